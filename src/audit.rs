@@ -0,0 +1,268 @@
+// Drop order auditing. For each lesson section there's a scenario below that is a copy of the
+// section's run(), with the same locals and statements but Noisy standing in for every String, so the
+// order in which they are freed can be logged. Where a lesson already uses Noisy, the scenario calls
+// the lesson's own functions instead. Rust drops a scope's locals in reverse order of declaration, the fields of a
+// struct or tuple in declaration order, and the elements of a vector in index order; values that were
+// moved away are dropped by their new owner instead, or not at all.
+//
+// Each scenario calls scope_exit() as its last statement, so the log splits into what was dropped
+// while the body ran and what was dropped by the closing brace.
+
+// The scenarios keep the lessons' Vecs even where an array would do.
+#![allow(clippy::useless_vec)]
+
+use std::rc::Rc;
+
+use crate::lessons::more_moves::{reassign, reassign_after_move};
+use crate::noisy::Noisy;
+use crate::trace::{self, Event};
+
+pub struct Audit {
+    pub during: Vec<String>,
+    pub at_exit: Vec<String>,
+}
+
+const SCOPE_EXIT: &str = "scope exit";
+
+fn scope_exit() {
+    trace::statement(SCOPE_EXIT);
+}
+
+pub fn audit(scenario: fn()) -> Audit {
+    trace::take_log();
+    scenario();
+    let mut audit = Audit { during: Vec::new(), at_exit: Vec::new() };
+    let mut exited = false;
+    for event in trace::take_log() {
+        match event {
+            Event::Statement(SCOPE_EXIT) => exited = true,
+            Event::NoisyDrop { text, .. } if exited => audit.at_exit.push(text),
+            Event::NoisyDrop { text, .. } => audit.during.push(text),
+            _ => {}
+        }
+    }
+    audit
+}
+
+pub fn print(title: &str, audit: &Audit) {
+    println!("{}", title);
+    println!("  dropped before the end of the scope:");
+    for (i, text) in audit.during.iter().enumerate() {
+        println!("    {}. {}", i + 1, text);
+    }
+    println!("  dropped at the end of the scope:");
+    for (i, text) in audit.at_exit.iter().enumerate() {
+        println!("    {}. {}", i + 1, text);
+    }
+}
+
+// Keyed by lesson section name.
+pub const SCENARIOS: &[(&str, fn())] = &[
+    ("moves", moves),
+    ("more-moves", more_moves),
+    ("control-flow", control_flow),
+    ("indexed-content", indexed_content),
+    ("copy-types", copy_types),
+    ("rc-arc", rc_arc),
+    ("drop-flags", drop_flags),
+];
+
+pub fn scenario(section: &str) -> Option<fn()> {
+    SCENARIOS.iter().find(|&&(name, _)| name == section).map(|&(_, scenario)| scenario)
+}
+
+// The noodle vector, with each string labelled by the variable that built it.
+fn noodles(owner: &str) -> Vec<Noisy> {
+    ["udon", "ramen", "soba"].iter()
+        .enumerate()
+        .map(|(i, noodle)| Noisy::new(&format!("{}[{}] {}", owner, i, noodle)))
+        .collect()
+}
+
+// The second s shadows the first without dropping anything: the first s's vector lives on in the
+// first t until the end of the scope, and is dropped after the second s's, and its clones, in t and u.
+fn moves() {
+    let s = noodles("first s");
+    let t = s;
+    let _ = &t;
+    let s = noodles("second s");
+    let t = s.clone();
+    let u = s.clone();
+    let _ = (&s, &t, &u);
+    scope_exit();
+}
+
+struct Person { name: Option<Noisy>, birth: i32 }
+
+// registry goes through the lesson's registry steps, with a Vec kept sorted by birth standing in for
+// ComposerRegistry: Palestrina is removed and outlives the rest, and the other names are moved out of
+// their Persons into names, so nothing is dropped until registry returns.
+#[allow(clippy::vec_init_then_push)]
+fn more_moves() {
+    fn registry(composers: Vec<Person>) {
+        let mut registry: Vec<Person> = Vec::new();
+        registry.extend(composers);
+        for (name, birth) in [("Dowland", 1563), ("Lully", 1632), ("Monteverdi", 1567)] {
+            let index = registry.partition_point(|c| c.birth <= birth);
+            registry.insert(index, Person { name: Some(Noisy::new(name)), birth });
+        }
+        let palestrina = registry.remove(0);
+        let everyone = registry;
+        let names: Vec<Noisy> = everyone.into_iter().filter_map(|c| c.name).collect();
+        let _ = (&palestrina, &names);
+    }
+    reassign();
+    reassign_after_move();
+    let mut composers = Vec::new();
+    composers.push(Person { name: Some(Noisy::new("composers[0].name Palestrina")), birth: 1525 });
+    registry(composers);
+    scope_exit();
+}
+
+fn control_flow() {
+    fn f(x: Vec<Noisy>) { drop(x); }
+    fn g(x: Vec<Noisy>) { drop(x); }
+    fn h() -> Vec<Noisy> { vec![Noisy::new("h() 40, 50, 60")] }
+    fn e(x: Vec<Noisy>) { drop(x); }
+
+    for c in [true, false] {
+        let x = vec![Noisy::new(&format!("x 10, 20, 30 with c = {}", c))];
+        if c { f(x) } else { g(x) }
+    }
+
+    let mut rounds = 2;
+    let mut x = vec![Noisy::new("x 10, 20, 30 before the loop")];
+    while rounds > 0 {
+        g(x);
+        x = h();
+        rounds -= 1;
+    }
+    e(x);
+    scope_exit();
+}
+
+// Each of x and y is moved on one branch only, so both need drop flags; whichever wasn't moved is
+// dropped at the end of the scope.
+fn drop_flags() {
+    fn f(x: Noisy) { drop(x); }
+    let c = std::hint::black_box(true);
+    let x = Noisy::new("x moved when c");
+    let y = Noisy::new("y moved unless c");
+    if c { f(x) } else { f(y) }
+    scope_exit();
+}
+
+fn indexed_content() {
+    let mut v: Vec<Noisy> = (101..106).map(|i| Noisy::new(&format!("v {}", i))).collect();
+    let fifth = v.pop().unwrap();
+    let second = v.swap_remove(1);
+    let third = std::mem::replace(&mut v[2], Noisy::new("v substitute"));
+
+    let v2 = vec![Noisy::new("liberte"), Noisy::new("egalite"), Noisy::new("fraternity")];
+    for s in v2 {
+        let _ = &s;
+    }
+
+    struct Person { name: Option<Noisy>, birth: i32 }
+    let mut composers = vec![Person { name: Some(Noisy::new("composers[0].name Palestrina")), birth: 1525 }];
+    let first_name = composers[0].name.take();
+    let _ = (&fifth, &second, &third, &first_name, &composers[0].birth);
+    scope_exit();
+}
+
+fn copy_types() {
+    let str1 = Noisy::new("str1 somnambulance");
+    let str2 = str1;
+    let num1: i32 = 36;
+    let num2 = num1;
+
+    struct Label { number: Noisy }
+    fn print(l: Label) { let _ = &l.number; }
+    let l = Label { number: Noisy::new("l.number 3") };
+    print(l);
+    let _ = (&str2, num2);
+    scope_exit();
+}
+
+// u and t go first, but only decrement the count; the string goes when s, the last pointer, does.
+fn rc_arc() {
+    let s: Rc<Noisy> = Rc::new(Noisy::new("shirataki"));
+    let t: Rc<Noisy> = s.clone();
+    let u: Rc<Noisy> = s.clone();
+    let _ = (&t, &u);
+    scope_exit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(section: &str, during: &[&str], at_exit: &[&str]) {
+        let audit = audit(scenario(section).unwrap());
+        assert_eq!(audit.during, during);
+        assert_eq!(audit.at_exit, at_exit);
+    }
+
+    #[test]
+    fn moves_drops_in_reverse_declaration_order() {
+        check("moves", &[], &[
+            "clone of second s[0] udon", "clone of second s[1] ramen", "clone of second s[2] soba",
+            "clone of second s[0] udon", "clone of second s[1] ramen", "clone of second s[2] soba",
+            "second s[0] udon", "second s[1] ramen", "second s[2] soba",
+            "first s[0] udon", "first s[1] ramen", "first s[2] soba",
+        ]);
+    }
+
+    #[test]
+    fn more_moves_drops_inside_the_callees() {
+        check("more-moves", &[
+            "Govinda", "Siddhartha", "Govinda", "Siddhartha",
+            "Dowland", "Monteverdi", "Lully", "composers[0].name Palestrina",
+        ], &[]);
+    }
+
+    #[test]
+    fn fields_drop_in_declaration_order() {
+        fn two_fields() {
+            struct Person { name: Noisy, nickname: Noisy }
+            let p = Person { nickname: Noisy::new("p.nickname"), name: Noisy::new("p.name") };
+            let _ = (&p.name, &p.nickname);
+            scope_exit();
+        }
+        let audit = audit(two_fields);
+        assert_eq!(audit.during, Vec::<String>::new());
+        assert_eq!(audit.at_exit, ["p.name", "p.nickname"]);
+    }
+
+    #[test]
+    fn control_flow_drops_inside_the_callees() {
+        check("control-flow", &[
+            "x 10, 20, 30 with c = true", "x 10, 20, 30 with c = false",
+            "x 10, 20, 30 before the loop", "h() 40, 50, 60", "h() 40, 50, 60",
+        ], &[]);
+    }
+
+    #[test]
+    fn drop_flags_drop_whatever_was_not_moved() {
+        check("drop-flags", &["x moved when c"], &["y moved unless c"]);
+    }
+
+    #[test]
+    fn indexed_content_drops_what_was_taken_before_the_vector() {
+        check("indexed-content", &["liberte", "egalite", "fraternity"], &[
+            "composers[0].name Palestrina",
+            "v 103", "v 102", "v 105",
+            "v 101", "v 104", "v substitute",
+        ]);
+    }
+
+    #[test]
+    fn copy_types_drops_the_moved_string_once() {
+        check("copy-types", &["l.number 3"], &["str1 somnambulance"]);
+    }
+
+    #[test]
+    fn rc_arc_drops_the_string_with_the_last_pointer() {
+        check("rc-arc", &[], &["shirataki"]);
+    }
+}
@@ -0,0 +1,123 @@
+// How cheap is a move? The notes say only the value proper moves, never the heap it owns, so moving a
+// Vec<String> copies a three-word header however long the vector is. That makes a move cost whatever
+// size_of says, which stops being cheap for a value that keeps its bulk inline, like a 4 KiB array,
+// and is cheap again once the array is behind a Box.
+//
+// Each benchmark moves one value many times through a function argument, a function return or
+// Vec::push. A move is a bitwise copy of the value that leaves the source uninitialized; ptr::read
+// makes exactly that copy, and wrapping the result in ManuallyDrop keeps the copy from being dropped
+// as a second owner. So no allocation or drop lands inside the timing, just the moves.
+//
+// The times only mean something in a release build:
+//     cargo run --release -- bench
+
+use std::hint::black_box;
+use std::mem::{self, ManuallyDrop};
+use std::ptr;
+use std::time::Instant;
+
+use crate::composers::Person;
+
+const ITERATIONS: usize = 200_000;
+// Values pushed before the vector is cleared again, so the 4 KiB array doesn't need 800 MB.
+const BATCH: usize = 1_000;
+// Each measurement is repeated and the fastest run kept, to keep scheduling noise out.
+const RUNS: usize = 5;
+
+pub struct Row {
+    pub value: &'static str,
+    // What one move copies: the value proper, not anything it owns on the heap.
+    pub bytes: usize,
+    // Nanoseconds per move through an argument, a return and Vec::push.
+    pub nanos: [f64; 3],
+}
+
+#[inline(never)]
+fn take_argument<T>(value: ManuallyDrop<T>) {
+    black_box(&value);
+}
+
+#[inline(never)]
+fn give_return<T>(source: &T) -> ManuallyDrop<T> {
+    // Safety: source is a valid reference, and the copy goes straight into a ManuallyDrop, so it's
+    // never dropped and the value is still dropped only through its original.
+    ManuallyDrop::new(unsafe { ptr::read(source) })
+}
+
+fn time(iterations: usize, mut step: impl FnMut()) -> f64 {
+    let mut best = f64::INFINITY;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            step();
+        }
+        best = best.min(start.elapsed().as_nanos() as f64 / iterations as f64);
+    }
+    best
+}
+
+fn measure<T>(value: &'static str, source: T, iterations: usize) -> Row {
+    let source = ManuallyDrop::new(source);
+    let source: &T = &source;
+    // Every copy made below is forgotten, so only the original is ever dropped, once, at the end.
+    let argument = time(iterations, || {
+        // Safety: the copy is wrapped in a ManuallyDrop before take_argument gets it, and take_argument
+        // only looks at it, so it's never dropped.
+        take_argument(ManuallyDrop::new(unsafe { ptr::read(black_box(source)) }));
+    });
+    let returned = time(iterations, || {
+        black_box(give_return(black_box(source)));
+    });
+    let mut pushed: Vec<ManuallyDrop<T>> = Vec::with_capacity(BATCH);
+    let push = time(iterations, || {
+        if pushed.len() == BATCH {
+            pushed.clear();
+        }
+        // Safety: the vector holds ManuallyDrops, so neither clear() nor dropping the vector drops the
+        // copies pushed into it.
+        pushed.push(ManuallyDrop::new(unsafe { ptr::read(black_box(source)) }));
+        black_box(&pushed);
+    });
+    // Safety: source is the ManuallyDrop that has owned the value all along, and this is the one copy
+    // of it that's ever dropped; source itself isn't used again.
+    drop(unsafe { ptr::read(source) });
+    Row { value, bytes: mem::size_of::<T>(), nanos: [argument, returned, push] }
+}
+
+fn rows(iterations: usize) -> Vec<Row> {
+    let noodles = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    vec![
+        measure("Vec<String>", noodles, iterations),
+        measure("Person", Person::new("Palestrina".to_string(), 1525), iterations),
+        measure("[u8; 4096]", [0u8; 4096], iterations),
+        measure("Box<[u8; 4096]>", Box::new([0u8; 4096]), iterations),
+    ]
+}
+
+pub fn print() {
+    if cfg!(debug_assertions) {
+        println!("(a debug build: run with --release for times worth comparing)");
+    }
+    println!("{:<18}{:>14}{:>14}{:>14}{:>14}", "ns per move", "bytes copied", "argument", "return", "Vec::push");
+    for row in rows(ITERATIONS) {
+        print!("{:<18}{:>14}", row.value, row.bytes);
+        for nanos in &row.nanos {
+            print!("{:>14.2}", nanos);
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_copied_is_the_value_proper() {
+        let word = mem::size_of::<usize>();
+        let rows = rows(10);
+        let bytes: Vec<(&str, usize)> = rows.iter().map(|row| (row.value, row.bytes)).collect();
+        assert_eq!(bytes, [("Vec<String>", 3 * word), ("Person", mem::size_of::<Person>()), ("[u8; 4096]", 4096), ("Box<[u8; 4096]>", word)]);
+        assert!(rows.iter().all(|row| row.nanos.iter().all(|nanos| nanos.is_finite())));
+    }
+}
@@ -0,0 +1,302 @@
+// The control flow graph of a checked program. Each let, assignment and expression statement is a
+// node, and so is each if or while condition, with one edge out for each way the condition can go.
+// Names are resolved to variables here, one per let, so a shadowing let is a different variable.
+
+use super::syntax::{self, ParseError, Projection, Stmt};
+
+pub type VarId = usize;
+
+// One step from a variable into its parts. Every index is the same step: the checker can't tell
+// v[1] from v[2], and doesn't need to, since neither can be moved out of.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Segment {
+    Field(String),
+    Index,
+}
+
+pub type Path = Vec<Segment>;
+
+pub struct Var {
+    pub name: String,
+    // The parts of the variable, if any, that hold Copy values, so that using them copies rather than
+    // moves. They come from the initializer: a bool or number, a struct field set to one, or the
+    // elements of a vector of them. An empty path is the variable as a whole.
+    pub copy: Vec<Path>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    pub var: VarId,
+    pub path: Path,
+    // The expressions inside the path's indexes, in order.
+    pub indexes: Vec<Expr>,
+    pub text: String,
+}
+
+impl Place {
+    // The part of the path that can be moved out of on its own: everything before the first index.
+    pub fn move_path(&self) -> &[Segment] {
+        let end = self.path.iter().position(|segment| *segment == Segment::Index).unwrap_or(self.path.len());
+        &self.path[..end]
+    }
+
+    pub fn through_index(&self) -> bool {
+        self.path.contains(&Segment::Index)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    // Moves the place's value, or copies it if it's Copy.
+    Place(Place),
+    // Reads the place without moving anything.
+    Borrow(Place),
+    Call(String, Vec<Expr>),
+    Vec(Vec<Expr>),
+    Struct(Vec<Expr>),
+    Literal(String),
+}
+
+// A place that an expression reads, and whether it's borrowed rather than moved.
+pub struct Use<'a> {
+    pub place: &'a Place,
+    pub borrow: bool,
+}
+
+impl Expr {
+    // The places the expression uses, in the order it uses them.
+    pub fn uses<'a>(&'a self, out: &mut Vec<Use<'a>>) {
+        match self {
+            Expr::Place(place) | Expr::Borrow(place) => {
+                for index in &place.indexes {
+                    index.uses(out);
+                }
+                out.push(Use { place, borrow: matches!(self, Expr::Borrow(_)) });
+            }
+            Expr::Call(_, items) | Expr::Vec(items) | Expr::Struct(items) => {
+                for item in items {
+                    item.uses(out);
+                }
+            }
+            Expr::Literal(_) => {}
+        }
+    }
+}
+
+pub enum Action {
+    Entry,
+    Exit,
+    Let(VarId, Expr),
+    Assign(Place, Expr),
+    Eval(Expr),
+    Branch(Expr),
+}
+
+pub struct Edge {
+    pub to: usize,
+    // How a condition went for control to take this edge, like "c = true".
+    pub label: Option<String>,
+    // From the end of a loop body back to the loop's condition.
+    pub back: bool,
+}
+
+pub struct Node {
+    // 0 for the entry and exit nodes.
+    pub line: usize,
+    pub text: String,
+    // How many blocks the statement is nested in.
+    pub depth: usize,
+    pub action: Action,
+    pub edges: Vec<Edge>,
+}
+
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub vars: Vec<Var>,
+}
+
+pub const ENTRY: usize = 0;
+
+impl Graph {
+    pub fn is_copy(&self, place: &Place) -> bool {
+        self.vars[place.var].copy.contains(&place.path)
+    }
+
+    // The path a use moves out of its variable, if it moves anything. A move out of an index isn't
+    // allowed, so it doesn't count.
+    pub fn moved_by<'a>(&self, used: &Use<'a>) -> Option<&'a [Segment]> {
+        if used.borrow || used.place.through_index() || self.is_copy(used.place) {
+            None
+        } else {
+            Some(used.place.move_path())
+        }
+    }
+
+    // The edges into node, with the nodes they leave from.
+    pub fn predecessors(&self, node: usize) -> impl Iterator<Item = (usize, &Edge)> + '_ {
+        self.nodes.iter().enumerate().flat_map(move |(from, n)| {
+            n.edges.iter().filter(move |edge| edge.to == node).map(move |edge| (from, edge))
+        })
+    }
+}
+
+// An edge waiting for the node it leads to: where it leaves from and how it's labelled.
+type Pending = (usize, Option<String>);
+
+struct Builder {
+    nodes: Vec<Node>,
+    vars: Vec<Var>,
+    scopes: Vec<Vec<(String, VarId)>>,
+}
+
+impl Builder {
+    fn add(&mut self, line: usize, text: String, action: Action, preds: Vec<Pending>) -> usize {
+        let id = self.nodes.len();
+        let depth = self.scopes.len().saturating_sub(1);
+        self.nodes.push(Node { line, text, depth, action, edges: Vec::new() });
+        self.link(preds, id, false);
+        id
+    }
+
+    fn link(&mut self, preds: Vec<Pending>, to: usize, back: bool) {
+        for (from, label) in preds {
+            self.nodes[from].edges.push(Edge { to, label, back });
+        }
+    }
+
+    fn resolve(&self, line: usize, name: &str) -> Result<VarId, ParseError> {
+        self.scopes.iter().rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(n, _)| n == name)
+            .map(|&(_, var)| var)
+            .ok_or(ParseError { line, message: format!("cannot find value `{}` in this scope", name) })
+    }
+
+    fn place(&self, line: usize, place: &syntax::Place) -> Result<Place, ParseError> {
+        let mut path = Vec::new();
+        let mut indexes = Vec::new();
+        for projection in &place.projections {
+            match projection {
+                Projection::Field(field) => path.push(Segment::Field(field.clone())),
+                Projection::Index(index) => {
+                    path.push(Segment::Index);
+                    indexes.push(self.expr(line, index)?);
+                }
+            }
+        }
+        Ok(Place { var: self.resolve(line, &place.name)?, path, indexes, text: place.to_string() })
+    }
+
+    fn expr(&self, line: usize, expr: &syntax::Expr) -> Result<Expr, ParseError> {
+        Ok(match expr {
+            syntax::Expr::Place(place) => Expr::Place(self.place(line, place)?),
+            syntax::Expr::Borrow(inner) => match &**inner {
+                syntax::Expr::Place(place) => Expr::Borrow(self.place(line, place)?),
+                // A reference to a temporary, like &f(x): whatever goes into the temporary moves.
+                other => self.expr(line, other)?,
+            },
+            syntax::Expr::Call(function, args) => Expr::Call(function.clone(), self.exprs(line, args)?),
+            syntax::Expr::Vec(items) => Expr::Vec(self.exprs(line, items)?),
+            syntax::Expr::Struct(_, fields) => {
+                let values: Vec<syntax::Expr> = fields.iter().map(|(_, value)| value.clone()).collect();
+                Expr::Struct(self.exprs(line, &values)?)
+            }
+            syntax::Expr::Literal(text) => Expr::Literal(text.clone()),
+        })
+    }
+
+    // The paths within a value of expr that hold Copy values.
+    fn copy_paths(&self, expr: &syntax::Expr) -> Vec<Path> {
+        let within = |segment: Segment, paths: Vec<Path>| -> Vec<Path> {
+            paths.into_iter().map(|path| std::iter::once(segment.clone()).chain(path).collect()).collect()
+        };
+        match expr {
+            syntax::Expr::Literal(text) if !text.starts_with('"') => vec![Vec::new()],
+            // Shared references are Copy, whatever they point to.
+            syntax::Expr::Borrow(_) => vec![Vec::new()],
+            syntax::Expr::Vec(items) => match items.first() {
+                Some(first) => within(Segment::Index, self.copy_paths(first)),
+                None => Vec::new(),
+            },
+            syntax::Expr::Struct(_, fields) => fields.iter()
+                .flat_map(|(field, value)| within(Segment::Field(field.clone()), self.copy_paths(value)))
+                .collect(),
+            // let q = p.name; copies whatever is Copy in p.name.
+            syntax::Expr::Place(place) => match self.place(0, place) {
+                Ok(place) => self.vars[place.var].copy.iter()
+                    .filter_map(|path| path.strip_prefix(place.path.as_slice()))
+                    .map(|rest| rest.to_vec())
+                    .collect(),
+                Err(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn exprs(&self, line: usize, exprs: &[syntax::Expr]) -> Result<Vec<Expr>, ParseError> {
+        exprs.iter().map(|expr| self.expr(line, expr)).collect()
+    }
+
+    fn block(&mut self, stmts: &[Stmt], preds: Vec<Pending>) -> Result<Vec<Pending>, ParseError> {
+        self.scopes.push(Vec::new());
+        let mut preds = preds;
+        for stmt in stmts {
+            preds = self.stmt(stmt, preds)?;
+        }
+        self.scopes.pop();
+        Ok(preds)
+    }
+
+    // Adds the nodes for stmt after preds, and returns the edges leaving it.
+    fn stmt(&mut self, stmt: &Stmt, preds: Vec<Pending>) -> Result<Vec<Pending>, ParseError> {
+        let node = match stmt {
+            Stmt::Let { line, name, mutable, value } => {
+                // The new variable isn't in scope in its own initializer.
+                let value_expr = self.expr(*line, value)?;
+                let var = self.vars.len();
+                let copy = self.copy_paths(value);
+                self.vars.push(Var { name: name.clone(), copy });
+                self.scopes.last_mut().unwrap().push((name.clone(), var));
+                let text = format!("let {}{} = {}", if *mutable { "mut " } else { "" }, name, value);
+                self.add(*line, text, Action::Let(var, value_expr), preds)
+            }
+            Stmt::Assign { line, place, value } => {
+                let action = Action::Assign(self.place(*line, place)?, self.expr(*line, value)?);
+                self.add(*line, format!("{} = {}", place, value), action, preds)
+            }
+            Stmt::Expr { line, expr } => {
+                let action = Action::Eval(self.expr(*line, expr)?);
+                self.add(*line, expr.to_string(), action, preds)
+            }
+            Stmt::Block(stmts) => return self.block(stmts, preds),
+            Stmt::If { line, condition, then, otherwise } => {
+                let action = Action::Branch(self.expr(*line, condition)?);
+                let branch = self.add(*line, format!("if {}", condition), action, preds);
+                let taken = |value: bool| (branch, Some(format!("{} = {}", condition, value)));
+                let mut exits = self.block(then, vec![taken(true)])?;
+                match otherwise {
+                    Some(otherwise) => exits.extend(self.block(otherwise, vec![taken(false)])?),
+                    None => exits.push(taken(false)),
+                }
+                return Ok(exits);
+            }
+            Stmt::While { line, condition, body } => {
+                let action = Action::Branch(self.expr(*line, condition)?);
+                let head = self.add(*line, format!("while {}", condition), action, preds);
+                let taken = |value: bool| (head, Some(format!("{} = {}", condition, value)));
+                let ends = self.block(body, vec![taken(true)])?;
+                self.link(ends, head, true);
+                return Ok(vec![taken(false)]);
+            }
+        };
+        Ok(vec![(node, None)])
+    }
+}
+
+pub fn build(program: &[Stmt]) -> Result<Graph, ParseError> {
+    let mut builder = Builder { nodes: Vec::new(), vars: Vec::new(), scopes: Vec::new() };
+    let entry = builder.add(0, "start".to_string(), Action::Entry, Vec::new());
+    let ends = builder.block(program, vec![(entry, None)])?;
+    builder.add(0, "end".to_string(), Action::Exit, ends);
+    Ok(Graph { nodes: builder.nodes, vars: builder.vars })
+}
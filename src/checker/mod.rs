@@ -0,0 +1,474 @@
+// A checker for the rule Moves and Control Flow states: if it's possible for a variable to have had
+// its value moved away, and it hasn't definitely been given a new value since, it's considered
+// uninitialized. The program is written in a small language of lets, assignments, calls, if/else,
+// while and blocks (see syntax.rs), turned into a control flow graph (flow.rs), and each variable's
+// state is pushed forward through the graph until nothing changes. Where two paths meet, a variable
+// initialized on one and moved on the other is Maybe-Moved, and using a variable in any state but
+// Initialized is an error, just as rustc's E0382 would have it. Each error comes with a run of the
+// program that leads to it (path.rs).
+//
+// A variable's state is kept per part: moving p.name moves only that field, leaving p.birth usable
+// while p as a whole is partially moved and can't be used or borrowed until p.name is assigned again.
+// Elements of a vector are different, since a vector can't have a hole in it: moving v[2] out is an
+// error of its own, and only Copy elements can be taken by index.
+
+pub mod flow;
+pub mod path;
+pub mod syntax;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use self::flow::{Action, Graph, Path, Place, Segment};
+use self::syntax::ParseError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Initialized,
+    Moved,
+    MaybeMoved,
+}
+
+impl State {
+    fn join(self, other: State) -> State {
+        if self == other { self } else { State::MaybeMoved }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            State::Initialized => "Initialized",
+            State::Moved => "Moved",
+            State::MaybeMoved => "Maybe-Moved",
+        })
+    }
+}
+
+// The parts of a variable that are Moved or MaybeMoved; any part not under one of them is
+// Initialized, so an empty map is a variable that holds its whole value.
+pub type Moves = BTreeMap<Path, State>;
+
+// Indexed by VarId. None until the variable's let has run.
+pub type States = Vec<Option<Moves>>;
+
+// The state of a part as a whole: whatever its nearest moved ancestor, or itself, says. Moved wins
+// over MaybeMoved, so p.name is Moved if it was moved outright, even if p as a whole only maybe was.
+fn moved<'a>(moves: &'a Moves, path: &[Segment]) -> Option<(&'a Path, State)> {
+    let mut found = None;
+    for (key, &state) in moves {
+        if path.starts_with(key) && !matches!(found, Some((_, State::Moved))) {
+            found = Some((key, state));
+        }
+    }
+    found
+}
+
+// Whether anything inside the part has been moved out of it, short of the whole part.
+fn partly(moves: &Moves, path: &[Segment]) -> Option<State> {
+    let mut found = None;
+    for (key, &state) in moves {
+        if key.len() > path.len() && key.starts_with(path) && found != Some(State::Moved) {
+            found = Some(state);
+        }
+    }
+    found
+}
+
+fn state_of(moves: &Moves, path: &[Segment]) -> State {
+    moved(moves, path).map_or(State::Initialized, |(_, state)| state)
+}
+
+fn join(a: &States, b: &States) -> States {
+    a.iter().zip(b).map(|(a, b)| match (a, b) {
+        (Some(a), Some(b)) => Some(a.keys().chain(b.keys())
+            .map(|path| (path.clone(), state_of(a, path).join(state_of(b, path))))
+            .filter(|(_, state)| *state != State::Initialized)
+            .collect()),
+        _ => None,
+    }).collect()
+}
+
+// Marks path as moved, along with everything inside it.
+fn move_out(moves: &mut Moves, path: &[Segment]) {
+    moves.retain(|key, _| !key.starts_with(path));
+    moves.insert(path.to_vec(), State::Moved);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    // The state of the part that was used: Moved or MaybeMoved.
+    UseOfMoved(State),
+    // The state of the part inside it that was moved.
+    UseOfPartiallyMoved(State),
+    MoveOutOfIndex { of: String },
+    AssignToPartOfMoved(State),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub node: usize,
+    // The place the statement uses or assigns.
+    pub place: Place,
+    // How the diagnostic names it, which for an assignment is the moved part it assigns into.
+    pub name: String,
+    pub problem: Problem,
+    // A run of the program that moves the value and then reaches the use, one step per entry.
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        let name = &self.name;
+        match &self.problem {
+            Problem::UseOfMoved(State::MaybeMoved) => write!(f, "use of possibly-moved value `{}`: it is moved on some paths here", name),
+            Problem::UseOfMoved(_) => write!(f, "use of moved value `{}`", name),
+            Problem::UseOfPartiallyMoved(State::MaybeMoved) => write!(f, "use of possibly partially moved value `{}`: part of it is moved on some paths here", name),
+            Problem::UseOfPartiallyMoved(_) => write!(f, "use of partially moved value `{}`", name),
+            Problem::MoveOutOfIndex { of } => write!(f, "cannot move out of index of `{}`; help: consider using a reference instead: `&{}`", of, name),
+            Problem::AssignToPartOfMoved(State::MaybeMoved) => write!(f, "assign to part of possibly-moved value `{}`", name),
+            Problem::AssignToPartOfMoved(_) => write!(f, "assign to part of moved value `{}`", name),
+        }
+    }
+}
+
+// How the checker writes a part of a variable, after the variable's name: .name, [_].name, or
+// nothing for the whole of it.
+fn suffix(path: &[Segment]) -> String {
+    path.iter().map(|segment| match segment {
+        Segment::Field(field) => format!(".{}", field),
+        Segment::Index => "[_]".to_string(),
+    }).collect()
+}
+
+fn describe(graph: &Graph, var: usize, path: &[Segment]) -> String {
+    format!("{}{}", graph.vars[var].name, suffix(path))
+}
+
+pub struct Analysis {
+    pub graph: Graph,
+    // The states on entry to each node; None for a node that can't be reached.
+    pub before: Vec<Option<States>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Runs node on states, calling report for each place the node can't use or assign as it does.
+fn apply(graph: &Graph, node: usize, states: &mut States, mut report: impl FnMut(&Place, String, Problem)) {
+    let expr = match &graph.nodes[node].action {
+        Action::Entry | Action::Exit => return,
+        Action::Let(_, expr) | Action::Assign(_, expr) | Action::Eval(expr) | Action::Branch(expr) => expr,
+    };
+    let mut uses = Vec::new();
+    expr.uses(&mut uses);
+    // Assigning into an element, v[i] = x, needs v whole, as borrowing it would.
+    if let Action::Assign(place, _) = &graph.nodes[node].action {
+        if place.through_index() {
+            for index in &place.indexes {
+                index.uses(&mut uses);
+            }
+            uses.push(flow::Use { place, borrow: true });
+        }
+    }
+    for used in &uses {
+        let place = used.place;
+        let moves = match &mut states[place.var] {
+            Some(moves) => moves,
+            None => continue,
+        };
+        let path = place.move_path();
+        // Reading or assigning an element needs the whole container, so the diagnostic names that.
+        let name = if place.through_index() { describe(graph, place.var, path) } else { place.text.clone() };
+        if let Some((_, state)) = moved(moves, path) {
+            report(place, name, Problem::UseOfMoved(state));
+        } else if let Some(state) = partly(moves, path).filter(|_| !graph.is_copy(place)) {
+            report(place, name, Problem::UseOfPartiallyMoved(state));
+        } else if !used.borrow && place.through_index() && !graph.is_copy(place) {
+            let of = describe(graph, place.var, path);
+            report(place, place.text.clone(), Problem::MoveOutOfIndex { of });
+        }
+        if let Some(path) = graph.moved_by(used) {
+            move_out(moves, path);
+        }
+    }
+
+    match &graph.nodes[node].action {
+        Action::Let(var, _) => states[*var] = Some(Moves::new()),
+        Action::Assign(place, _) if !place.through_index() => {
+            let moves = match &mut states[place.var] {
+                Some(moves) => moves,
+                None => return,
+            };
+            // A field can only be given a new value inside a struct that's still there.
+            let enclosing = moved(moves, &place.path).filter(|(key, _)| key.len() < place.path.len());
+            if let Some((key, state)) = enclosing {
+                let name = describe(graph, place.var, key);
+                report(place, name, Problem::AssignToPartOfMoved(state));
+            } else {
+                moves.retain(|key, _| !key.starts_with(&place.path));
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn analyze(graph: Graph) -> Analysis {
+    let count = graph.nodes.len();
+    let mut before: Vec<Option<States>> = vec![None; count];
+    before[flow::ENTRY] = Some(vec![None; graph.vars.len()]);
+
+    let mut worklist = vec![flow::ENTRY];
+    while let Some(node) = worklist.pop() {
+        let mut states = before[node].clone().expect("only reached nodes are queued");
+        apply(&graph, node, &mut states, |_, _, _| {});
+        for next in graph.nodes[node].edges.iter().map(|edge| edge.to) {
+            let joined = match &before[next] {
+                Some(existing) => join(existing, &states),
+                None => states.clone(),
+            };
+            if before[next].as_ref() != Some(&joined) {
+                before[next] = Some(joined);
+                worklist.push(next);
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for (node, states) in before.iter().enumerate() {
+        if let Some(states) = states {
+            let mut states = states.clone();
+            apply(&graph, node, &mut states, |place, name, problem| {
+                let line = graph.nodes[node].line;
+                diagnostics.push(Diagnostic { line, node, place: place.clone(), name, problem, path: Vec::new() });
+            });
+        }
+    }
+    diagnostics.sort_by_key(|d| (d.line, d.node));
+    for diagnostic in &mut diagnostics {
+        if let Problem::MoveOutOfIndex { .. } = diagnostic.problem {
+            continue;
+        }
+        let (node, place) = (diagnostic.node, &diagnostic.place);
+        // Nothing in the place was moved on the way in, so the statement itself moved it.
+        let moved_here = before[node].as_ref()
+            .and_then(|states| states[place.var].as_ref())
+            .is_some_and(|moves| !moves.keys().any(|key| key.starts_with(place.move_path()) || place.move_path().starts_with(key)));
+        diagnostic.path = path::counterexample(&graph, node, place, moved_here);
+    }
+    Analysis { graph, before, diagnostics }
+}
+
+pub fn check(source: &str) -> Result<Analysis, ParseError> {
+    let program = syntax::parse(source)?;
+    Ok(analyze(flow::build(&program)?))
+}
+
+// A cell of the table: the variable's state, with any parts of it in a different state after it,
+// like "Initialized, .name Moved".
+fn cell(moves: &Moves) -> String {
+    let mut parts = Vec::new();
+    if !moves.contains_key(&Vec::new()) {
+        parts.push(State::Initialized.to_string());
+    }
+    for (path, state) in moves {
+        if path.is_empty() {
+            parts.push(state.to_string());
+        } else {
+            parts.push(format!("{} {}", suffix(path), state));
+        }
+    }
+    parts.join(", ")
+}
+
+// The table's rows: each statement with the state of every variable as it starts, then the states
+// at the end of the program.
+pub fn table(analysis: &Analysis) -> Vec<(&flow::Node, Vec<Option<String>>)> {
+    analysis.graph.nodes.iter()
+        .zip(&analysis.before)
+        .filter(|(node, _)| !matches!(node.action, Action::Entry))
+        .map(|(node, states)| {
+            let cells = (0..analysis.graph.vars.len())
+                .map(|var| states.as_ref().and_then(|states| states[var].as_ref()).map(cell))
+                .collect();
+            (node, cells)
+        })
+        .collect()
+}
+
+pub fn print_table(analysis: &Analysis) {
+    let vars = &analysis.graph.vars;
+    let rows = table(analysis);
+    let texts: Vec<String> = rows.iter().map(|(node, _)| format!("{}{}", "  ".repeat(node.depth), node.text)).collect();
+    let text_width = texts.iter().map(|text| text.len()).max().unwrap_or(0);
+    let widths: Vec<usize> = vars.iter().enumerate().map(|(var, v)| {
+        let cells = rows.iter().filter_map(|(_, cells)| cells[var].as_ref()).map(|cell| cell.len());
+        cells.fold(v.name.len(), usize::max)
+    }).collect();
+
+    let mut header = format!("    {:>4}  {:<width$}", "line", "statement", width = text_width);
+    for (var, width) in vars.iter().zip(&widths) {
+        header.push_str(&format!("  {:<width$}", var.name, width = width));
+    }
+    println!("{}", header.trim_end());
+    for ((node, cells), text) in rows.iter().zip(&texts) {
+        let line = if node.line == 0 { String::new() } else { node.line.to_string() };
+        let mut row = format!("    {:>4}  {:<width$}", line, text, width = text_width);
+        for (cell, width) in cells.iter().zip(&widths) {
+            row.push_str(&format!("  {:<width$}", cell.as_deref().unwrap_or(""), width = width));
+        }
+        println!("{}", row.trim_end());
+    }
+}
+
+// Checks each of a lesson's snippets, printing the state of every variable as each statement starts
+// and then whatever the rule rejects.
+pub fn print_snippets(snippets: &[(&str, &str)]) {
+    for (title, source) in snippets {
+        println!("  {}:", title);
+        match check(source) {
+            Ok(analysis) => {
+                print_table(&analysis);
+                print(&analysis);
+            }
+            Err(e) => println!("    {}", e),
+        }
+    }
+}
+
+// Prints each diagnostic over the statement it's about, or says the program is accepted.
+pub fn print(analysis: &Analysis) {
+    if analysis.diagnostics.is_empty() {
+        println!("    accepted: every value is initialized where it's used");
+    }
+    for diagnostic in &analysis.diagnostics {
+        println!("    error: {}", diagnostic);
+        println!("        {}", analysis.graph.nodes[diagnostic.node].text);
+        if !diagnostic.path.is_empty() {
+            println!("    path: {}", diagnostic.path.join(" → "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostics(source: &str) -> Vec<String> {
+        check(source).unwrap().diagnostics.iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn both_branches_may_move() {
+        let source = "let c = true;\nlet x = vec![10, 20, 30];\nif c {\n    f(x);\n} else {\n    g(x);\n}\n";
+        assert_eq!(diagnostics(source), Vec::<String>::new());
+        assert_eq!(diagnostics(&format!("{}h(x);\n", source)), ["line 8: use of moved value `x`"]);
+    }
+
+    #[test]
+    fn one_branch_moves() {
+        let source = "let c = true;\nlet x = vec![10, 20, 30];\nif c { f(x); }\nh(x);\n";
+        assert_eq!(diagnostics(source), ["line 4: use of possibly-moved value `x`: it is moved on some paths here"]);
+    }
+
+    #[test]
+    fn loops_must_reinitialize() {
+        let moves = "let x = vec![10, 20, 30];\nwhile f() {\n    g(x);\n}\n";
+        assert_eq!(diagnostics(moves), ["line 3: use of possibly-moved value `x`: it is moved on some paths here"]);
+        let reinitializes = "let mut x = vec![10, 20, 30];\nwhile f() {\n    g(x);\n    x = h();\n}\ne(x);\n";
+        assert_eq!(diagnostics(reinitializes), Vec::<String>::new());
+    }
+
+    fn paths(source: &str) -> Vec<String> {
+        check(source).unwrap().diagnostics.iter().map(|d| d.path.join(" → ")).collect()
+    }
+
+    #[test]
+    fn paths_lead_to_the_use() {
+        let branches = "let c = true;\nlet x = vec![10, 20, 30];\nif c {\n    f(x);\n} else {\n    g(x);\n}\nh(x);\n";
+        assert_eq!(paths(branches), ["c = true → f(x) moved x at line 4 → h(x) uses x at line 8"]);
+        let one_branch = "let c = true;\nlet x = vec![10, 20, 30];\nif c { f(x); }\nh(x);\n";
+        assert_eq!(paths(one_branch), ["c = true → f(x) moved x at line 3 → h(x) uses x at line 4"]);
+        let twice = "let c = true;\nlet x = vec![10, 20, 30];\nif c { f(x, x); }\n";
+        assert_eq!(paths(twice), ["c = true → f(x, x) moves x and then uses it again at line 3"]);
+    }
+
+    #[test]
+    fn paths_count_loop_iterations() {
+        let moves = "let x = vec![10, 20, 30];\nwhile f() {\n    g(x);\n}\n";
+        assert_eq!(paths(moves), ["f() = true → iteration 1: g(x) moved x at line 3 → f() = true → iteration 2: g(x) uses x at line 3"]);
+        let after = "let c = true;\nlet mut x = vec![10, 20, 30];\nwhile f() {\n    if c { x = h(); } else { g(x); }\n}\n";
+        assert_eq!(paths(after), [
+            "f() = true → c = false → iteration 1: g(x) moved x at line 4 → f() = true → c = false → iteration 2: g(x) uses x at line 4",
+        ]);
+    }
+
+    #[test]
+    fn table_follows_the_loop() {
+        let analysis = check("let mut x = vec![10, 20, 30];\nwhile f() {\n    g(x);\n    x = h();\n}\ne(x);\n").unwrap();
+        let rows: Vec<(&str, Option<String>)> = table(&analysis).into_iter()
+            .map(|(node, mut cells)| (node.text.as_str(), cells.remove(0)))
+            .collect();
+        assert_eq!(rows, [
+            ("let mut x = vec![10, 20, 30]", None),
+            ("while f()", Some("Initialized".to_string())),
+            ("g(x)", Some("Initialized".to_string())),
+            ("x = h()", Some("Moved".to_string())),
+            ("e(x)", Some("Initialized".to_string())),
+            ("end", Some("Moved".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn fields_move_on_their_own() {
+        let source = "let mut p = Person { name: \"Palestrina\", birth: 1525 };\nf(p.name);\ng(p.birth);\nh(&p);\n";
+        assert_eq!(diagnostics(source), ["line 4: use of partially moved value `p`"]);
+        let c = "let c = true;\nlet p = Person { name: \"Palestrina\", birth: 1525 };\nif c { f(p.name); }\ng(p);\n";
+        assert_eq!(diagnostics(c), ["line 4: use of possibly partially moved value `p`: part of it is moved on some paths here"]);
+        assert_eq!(paths(c), ["c = true → f(p.name) moved p.name at line 3 → g(p) uses p at line 4"]);
+        let restored = "let mut p = Person { name: \"Palestrina\", birth: 1525 };\nf(p.name);\np.name = h();\ng(p);\n";
+        assert_eq!(diagnostics(restored), Vec::<String>::new());
+        let whole = "let mut p = Person { name: \"Palestrina\", birth: 1525 };\nf(p);\ng(p.birth);\np.name = h();\n";
+        assert_eq!(diagnostics(whole), ["line 3: use of moved value `p.birth`", "line 4: assign to part of moved value `p`"]);
+        let analysis = check("let p = Person { name: \"Palestrina\", birth: 1525 };\nf(p.name);\n").unwrap();
+        assert_eq!(table(&analysis).last().unwrap().1[0].as_deref(), Some("Initialized, .name Moved"));
+    }
+
+    #[test]
+    fn elements_cannot_be_moved_out() {
+        let source = "let v = vec![\"a\", \"b\", \"c\"];\nlet third = v[2];\nf(&v[2]);\ng(v);\n";
+        assert_eq!(diagnostics(source), [
+            "line 2: cannot move out of index of `v`; help: consider using a reference instead: `&v[2]`",
+        ]);
+        let fields = "let i = 0;\nlet people = vec![Person { name: \"Palestrina\", birth: 1525 }];\nf(people[i].name);\ng(people[i].birth);\n";
+        assert_eq!(diagnostics(fields), [
+            "line 3: cannot move out of index of `people`; help: consider using a reference instead: `&people[i].name`",
+        ]);
+        assert_eq!(diagnostics("let v = vec![1, 2, 3];\nlet n = v[2];\nf(v[0], v[1]);\ng(v);\n"), Vec::<String>::new());
+        assert_eq!(diagnostics("let mut v = vec![\"a\"];\nf(v);\nv[0] = \"b\";\ng(&v[0].len);\n"), [
+            "line 3: use of moved value `v`",
+            "line 4: use of moved value `v`",
+        ]);
+    }
+
+    #[test]
+    fn shadowing_and_copies() {
+        assert_eq!(diagnostics("let n = 1; f(n); f(n); let s = \"a\"; { let s = 2; f(s); f(s); } f(s); f(s);"),
+                   ["line 1: use of moved value `s`"]);
+        assert!(diagnostics("let x = vec![1]; let r = &x; f(r); f(r);").is_empty());
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        assert_eq!(check("let x = vec![];\nif x {\n").err().unwrap().to_string(), "line 2: expected '}', found the end of the program");
+        assert_eq!(check("let x = y;").err().unwrap().to_string(), "line 1: cannot find value `y` in this scope");
+        assert_eq!(check("let = 1;").err().unwrap().to_string(), "line 1: expected a name, found '='");
+    }
+
+    #[test]
+    fn tokens_outside_ascii() {
+        assert_eq!(check("let x = 1é;").err().unwrap().to_string(), "line 1: cannot find value `é` in this scope");
+        assert_eq!(check("let s = \"http://x\"; // a comment\nf(s);\nf(s); // \"unterminated\n").unwrap().diagnostics.len(), 1);
+        assert_eq!(check("let s = \"é\" / 2;").err().unwrap().to_string(), "line 1: unexpected '/'");
+    }
+}
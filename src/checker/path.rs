@@ -0,0 +1,137 @@
+// Counterexamples for the checker's diagnostics. "Use of possibly-moved value" says that some run of
+// the program moves the value before the use, but not which; this finds one. It searches backwards
+// from the use for the nearest statement that moves the variable, or a part of it that overlaps the
+// part used, without it being assigned again in between, then forwards from the start of the program
+// to that statement, and tells the combined path as the branches it takes, the move and the use. A
+// path that goes round a loop numbers its iterations, since "iteration 1 moves x, iteration 2 uses x"
+// is the whole story there.
+
+use std::collections::VecDeque;
+
+use super::flow::{Action, Edge, Graph, Place, ENTRY};
+
+// The place the statement at node moves out of used's variable, if it moves anything used needs:
+// used itself, something inside it, or something it's inside.
+fn moves<'a>(graph: &'a Graph, node: usize, used: &Place) -> Option<&'a Place> {
+    let expr = match &graph.nodes[node].action {
+        Action::Let(_, expr) | Action::Assign(_, expr) | Action::Eval(expr) | Action::Branch(expr) => expr,
+        Action::Entry | Action::Exit => return None,
+    };
+    let mut uses = Vec::new();
+    expr.uses(&mut uses);
+    let path = used.move_path();
+    uses.into_iter()
+        .filter(|u| u.place.var == used.var)
+        .find(|u| graph.moved_by(u).is_some_and(|moved| moved.starts_with(path) || path.starts_with(moved)))
+        .map(|u| u.place)
+}
+
+// Whether the statement at node gives all of used a new value.
+fn assigns(graph: &Graph, node: usize, used: &Place) -> bool {
+    match &graph.nodes[node].action {
+        Action::Let(var, _) => *var == used.var,
+        Action::Assign(place, _) => {
+            place.var == used.var && !place.through_index() && used.move_path().starts_with(&place.path)
+        }
+        _ => false,
+    }
+}
+
+// Edges along a run, each with the node it leaves from.
+type Steps<'a> = Vec<(usize, &'a Edge)>;
+
+// The edges of a shortest path from the entry to target.
+fn from_entry(graph: &Graph, target: usize) -> Steps<'_> {
+    let mut came_by: Vec<Option<(usize, &Edge)>> = vec![None; graph.nodes.len()];
+    let mut queue = VecDeque::from(vec![ENTRY]);
+    while let Some(node) = queue.pop_front() {
+        if node == target {
+            break;
+        }
+        for edge in &graph.nodes[node].edges {
+            if edge.to != ENTRY && came_by[edge.to].is_none() {
+                came_by[edge.to] = Some((node, edge));
+                queue.push_back(edge.to);
+            }
+        }
+    }
+    let mut path = Vec::new();
+    let mut node = target;
+    while let Some((from, edge)) = came_by[node] {
+        path.push((from, edge));
+        node = from;
+    }
+    path.reverse();
+    path
+}
+
+// The nearest statement before node that moves used with nothing assigning used in between, what it
+// moves, and the edges from it to node.
+fn from_move<'a>(graph: &'a Graph, node: usize, used: &Place) -> Option<(usize, &'a Place, Steps<'a>)> {
+    let mut goes_to: Vec<Option<(usize, &Edge)>> = vec![None; graph.nodes.len()];
+    let mut queue = VecDeque::from(vec![node]);
+    while let Some(current) = queue.pop_front() {
+        for (from, edge) in graph.predecessors(current) {
+            if goes_to[from].is_some() {
+                continue;
+            }
+            goes_to[from] = Some((current, edge));
+            if assigns(graph, from, used) {
+                continue;
+            }
+            if let Some(moved) = moves(graph, from, used) {
+                let mut path = Vec::new();
+                let mut at = from;
+                while let Some((next, edge)) = goes_to[at] {
+                    path.push((at, edge));
+                    if next == node {
+                        break;
+                    }
+                    at = next;
+                }
+                return Some((from, moved, path));
+            }
+            queue.push_back(from);
+        }
+    }
+    None
+}
+
+// The steps of a run that ends in the use of used at node. moved_here is for a statement that uses
+// the place twice, like f(x, x), where the move and the use are both at node.
+pub fn counterexample(graph: &Graph, node: usize, used: &Place, moved_here: bool) -> Vec<String> {
+    let at = &graph.nodes[node];
+    if moved_here {
+        let mut steps: Vec<String> = from_entry(graph, node).iter().filter_map(|(_, edge)| edge.label.clone()).collect();
+        steps.push(format!("{} moves {} and then uses it again at line {}", at.text, used.text, at.line));
+        return steps;
+    }
+    let (moving, moved, rest) = match from_move(graph, node, used) {
+        Some(found) => found,
+        None => return Vec::new(),
+    };
+    let mut path = from_entry(graph, moving);
+    let moved_after = path.len();
+    path.extend(rest);
+
+    let loops = path.iter().any(|(_, edge)| edge.back);
+    let mut iteration = 1;
+    let in_iteration = |iteration: usize, text: String| {
+        if loops { format!("iteration {}: {}", iteration, text) } else { text }
+    };
+    let mut steps = Vec::new();
+    for (i, (_, edge)) in path.iter().enumerate() {
+        if i == moved_after {
+            let moving = &graph.nodes[moving];
+            steps.push(in_iteration(iteration, format!("{} moved {} at line {}", moving.text, moved.text, moving.line)));
+        }
+        if edge.back {
+            iteration += 1;
+        }
+        if let Some(label) = &edge.label {
+            steps.push(label.clone());
+        }
+    }
+    steps.push(in_iteration(iteration, format!("{} uses {} at line {}", at.text, used.text, at.line)));
+    steps
+}
@@ -0,0 +1,421 @@
+// The checker's language: just enough Rust to write the chapter's control-flow examples.
+//     program := stmt*
+//     stmt    := "let" ["mut"] name "=" expr ";" | place "=" expr ";" | expr ";"
+//              | "if" expr block ["else" (block | if-stmt)] | "while" expr block | block
+//     block   := "{" stmt* "}"
+//     place   := name ("." name | "[" expr "]")*
+//     expr    := name "(" [expr ("," expr)*] ")" | place | "&" expr | "vec!" "[" [expr ("," expr)*] "]"
+//              | Name "{" [name ":" expr ("," name ":" expr)*] "}" | number | string | "true" | "false"
+// Semicolons after a statement are optional, as they are before a closing brace in Rust, and //
+// comments run to the end of the line. Every call is to a function the checker knows nothing about
+// except that it takes ownership of its arguments. A struct is written with a capitalized name, and
+// as in Rust it can't be written in an if or while condition, where its brace would be ambiguous.
+
+use std::error;
+use std::fmt;
+use std::mem;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    Field(String),
+    Index(Expr),
+}
+
+// A variable, or a part of one reached through fields and indexes, like composers[0].name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    pub name: String,
+    pub projections: Vec<Projection>,
+}
+
+impl fmt::Display for Place {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)?;
+        for projection in &self.projections {
+            match projection {
+                Projection::Field(field) => write!(f, ".{}", field)?,
+                Projection::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Place(Place),
+    Borrow(Box<Expr>),
+    Call(String, Vec<Expr>),
+    Vec(Vec<Expr>),
+    Struct(String, Vec<(String, Expr)>),
+    // Numbers, strings, true and false, kept as written.
+    Literal(String),
+}
+
+fn list(f: &mut fmt::Formatter, items: &[Expr]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Place(place) => write!(f, "{}", place),
+            Expr::Borrow(expr) => write!(f, "&{}", expr),
+            Expr::Call(function, args) => {
+                write!(f, "{}(", function)?;
+                list(f, args)?;
+                f.write_str(")")
+            }
+            Expr::Vec(items) => {
+                f.write_str("vec![")?;
+                list(f, items)?;
+                f.write_str("]")
+            }
+            Expr::Struct(name, fields) => {
+                write!(f, "{} {{ ", name)?;
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", field, value)?;
+                }
+                f.write_str(" }")
+            }
+            Expr::Literal(text) => f.write_str(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let { line: usize, name: String, mutable: bool, value: Expr },
+    Assign { line: usize, place: Place, value: Expr },
+    Expr { line: usize, expr: Expr },
+    If { line: usize, condition: Expr, then: Vec<Stmt>, otherwise: Option<Vec<Stmt>> },
+    While { line: usize, condition: Expr, body: Vec<Stmt> },
+    Block(Vec<Stmt>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Literal(String),
+    Punct(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    for (i, text) in source.lines().enumerate() {
+        let line = i + 1;
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            // Strings are read whole below, so a // here is outside one and starts a comment.
+            if c == '/' && chars.peek().is_some_and(|&(_, next)| next == '/') {
+                break;
+            }
+            if c.is_alphabetic() || c == '_' {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &text[start..end];
+                let token = if word == "true" || word == "false" { Token::Literal(word.to_string()) } else { Token::Name(word.to_string()) };
+                tokens.push((line, token));
+            } else if c.is_ascii_digit() {
+                // Digits, separators and suffixes like 1_000u32 are all ASCII, so each is one byte.
+                let mut end = start + 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                tokens.push((line, Token::Literal(text[start..end].to_string())));
+            } else if c == '"' {
+                let end = text[start + 1..].find('"')
+                    .ok_or(ParseError { line, message: "unterminated string".to_string() })?;
+                let end = start + 1 + end;
+                while chars.peek().is_some_and(|&(i, _)| i <= end) {
+                    chars.next();
+                }
+                tokens.push((line, Token::Literal(text[start..=end].to_string())));
+            } else if "{}()[];,=!.&:".contains(c) {
+                tokens.push((line, Token::Punct(c)));
+            } else {
+                return Err(ParseError { line, message: format!("unexpected {:?}", c) });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // Set while parsing an if or while condition.
+    no_struct: bool,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    // The line of the next token, or of the last one at the end of the input.
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or_else(|| self.tokens.last()).map_or(1, |&(line, _)| line)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError { line: self.line(), message })
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, token)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn at_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Name(name)) if name == keyword)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.at_punct(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            let found = self.describe_next();
+            self.error(format!("expected '{}', found {}", c, found))
+        }
+    }
+
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            Some(Token::Name(name)) => format!("`{}`", name),
+            Some(Token::Literal(text)) => format!("`{}`", text),
+            Some(Token::Punct(c)) => format!("'{}'", c),
+            None => "the end of the program".to_string(),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => {
+                let found = self.describe_next();
+                self.error(format!("expected a name, found {}", found))
+            }
+        }
+    }
+
+    fn skip_semicolon(&mut self) {
+        if self.at_punct(';') {
+            self.pos += 1;
+        }
+    }
+
+    fn stmts_until_brace(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut stmts = Vec::new();
+        while !self.at_punct('}') {
+            if self.peek().is_none() {
+                return self.error("expected '}', found the end of the program".to_string());
+            }
+            stmts.push(self.stmt()?);
+        }
+        Ok(stmts)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.expect('{')?;
+        let stmts = self.stmts_until_brace()?;
+        self.expect('}')?;
+        Ok(stmts)
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.line();
+        if self.at_punct('{') {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        if self.at_keyword("let") {
+            self.pos += 1;
+            let mutable = self.at_keyword("mut");
+            if mutable {
+                self.pos += 1;
+            }
+            let name = self.name()?;
+            self.expect('=')?;
+            let value = self.expr()?;
+            self.skip_semicolon();
+            return Ok(Stmt::Let { line, name, mutable, value });
+        }
+        if self.at_keyword("if") {
+            return self.if_stmt();
+        }
+        if self.at_keyword("while") {
+            self.pos += 1;
+            let condition = self.condition()?;
+            let body = self.block()?;
+            return Ok(Stmt::While { line, condition, body });
+        }
+        let expr = self.expr()?;
+        if let Expr::Place(place) = &expr {
+            if self.at_punct('=') {
+                self.pos += 1;
+                let value = self.expr()?;
+                self.skip_semicolon();
+                return Ok(Stmt::Assign { line, place: place.clone(), value });
+            }
+        }
+        self.skip_semicolon();
+        Ok(Stmt::Expr { line, expr })
+    }
+
+    fn if_stmt(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.line();
+        self.pos += 1;
+        let condition = self.condition()?;
+        let then = self.block()?;
+        let otherwise = if self.at_keyword("else") {
+            self.pos += 1;
+            if self.at_keyword("if") {
+                Some(vec![self.if_stmt()?])
+            } else {
+                Some(self.block()?)
+            }
+        } else {
+            None
+        };
+        Ok(Stmt::If { line, condition, then, otherwise })
+    }
+
+    fn exprs_until(&mut self, close: char) -> Result<Vec<Expr>, ParseError> {
+        // Inside brackets a struct is unambiguous again, even in a condition. (An error ends the
+        // parse, so there's no need to restore the flag on the way out.)
+        let no_struct = mem::replace(&mut self.no_struct, false);
+        let mut items = Vec::new();
+        while !self.at_punct(close) {
+            items.push(self.expr()?);
+            if !self.at_punct(close) {
+                self.expect(',')?;
+            }
+        }
+        self.no_struct = no_struct;
+        self.pos += 1;
+        Ok(items)
+    }
+
+    fn condition(&mut self) -> Result<Expr, ParseError> {
+        self.no_struct = true;
+        let condition = self.expr();
+        self.no_struct = false;
+        condition
+    }
+
+    fn place(&mut self, name: String) -> Result<Place, ParseError> {
+        let mut projections = Vec::new();
+        loop {
+            if self.at_punct('.') {
+                self.pos += 1;
+                projections.push(Projection::Field(self.name()?));
+            } else if self.at_punct('[') {
+                self.pos += 1;
+                let no_struct = mem::replace(&mut self.no_struct, false);
+                projections.push(Projection::Index(self.expr()?));
+                self.no_struct = no_struct;
+                self.expect(']')?;
+            } else {
+                return Ok(Place { name, projections });
+            }
+        }
+    }
+
+    fn struct_fields(&mut self) -> Result<Vec<(String, Expr)>, ParseError> {
+        let mut fields = Vec::new();
+        while !self.at_punct('}') {
+            let field = self.name()?;
+            self.expect(':')?;
+            fields.push((field, self.expr()?));
+            if !self.at_punct('}') {
+                self.expect(',')?;
+            }
+        }
+        self.pos += 1;
+        Ok(fields)
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        match self.next() {
+            Some(Token::Literal(text)) => Ok(Expr::Literal(text)),
+            Some(Token::Punct('&')) => Ok(Expr::Borrow(Box::new(self.expr()?))),
+            Some(Token::Name(name)) if name == "vec" && self.at_punct('!') => {
+                self.pos += 1;
+                self.expect('[')?;
+                Ok(Expr::Vec(self.exprs_until(']')?))
+            }
+            Some(Token::Name(name)) if self.at_punct('(') => {
+                self.pos += 1;
+                Ok(Expr::Call(name, self.exprs_until(')')?))
+            }
+            Some(Token::Name(name)) if !self.no_struct && name.starts_with(char::is_uppercase) && self.at_punct('{') => {
+                self.pos += 1;
+                Ok(Expr::Struct(name, self.struct_fields()?))
+            }
+            Some(Token::Name(name)) => Ok(Expr::Place(self.place(name)?)),
+            _ => {
+                self.pos -= 1;
+                let found = self.describe_next();
+                self.error(format!("expected an expression, found {}", found))
+            }
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Stmt>, ParseError> {
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0, no_struct: false };
+    let mut program = Vec::new();
+    while parser.peek().is_some() {
+        if parser.at_punct('}') {
+            return parser.error("unmatched '}'".to_string());
+        }
+        program.push(parser.stmt()?);
+    }
+    Ok(program)
+}
@@ -0,0 +1,189 @@
+// Checking "the machine code often stores the value directly where it belongs" against the compiler
+// we actually have. The composers.push(Person { ... }) line from More Operations That Move is compiled
+// on its own by the local rustc in release mode, and the generated code for it is searched for the
+// three ways the Person could get into the vector: stored straight into the vector's buffer, built in
+// a stack temporary first, or copied over with memcpy.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+const SNIPPET: &str = "\
+pub struct Person { pub name: Option<String>, pub birth: i32 }
+
+#[no_mangle]
+pub fn push_composer(composers: &mut Vec<Person>, name: String) {
+    composers.push(Person { name: Some(name), birth: 1525 });
+}
+";
+
+const FUNCTION: &str = "push_composer";
+
+// The one field whose value is a constant, so the store that writes it can be picked out.
+const BIRTH: &str = "1525";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emit {
+    Asm,
+    LlvmIr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    InPlace,
+    ViaStack,
+    Memcpy,
+    // No memcpy, but the birth store couldn't be found, e.g. because the target's assembly loads the
+    // constant into a register first.
+    NoMemcpy,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Verdict::InPlace => "constructed in place: the fields are stored straight into the vector's buffer",
+            Verdict::ViaStack => "built in a stack temporary, then copied into the vector's buffer",
+            Verdict::Memcpy => "copied into the vector's buffer with memcpy",
+            Verdict::NoMemcpy => "no memcpy, though the store of the birth year couldn't be picked out",
+        })
+    }
+}
+
+// Compiles the snippet in dir, with $RUSTC if it's set, and returns the assembly or LLVM IR.
+pub fn compile(emit: Emit, dir: &Path) -> io::Result<String> {
+    fs::create_dir_all(dir)?;
+    let source = dir.join("push_composer.rs");
+    fs::write(&source, SNIPPET)?;
+    let (kind, extension) = match emit {
+        Emit::Asm => ("asm", "s"),
+        Emit::LlvmIr => ("llvm-ir", "ll"),
+    };
+    let output = dir.join(format!("push_composer.{}", extension));
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let result = Command::new(rustc)
+        .args(["--edition", "2018", "--crate-type", "lib", "-C", "opt-level=3", "-C", "debuginfo=0"])
+        .arg(format!("--emit={}", kind))
+        .arg("-o").arg(&output)
+        .arg(&source)
+        .output()?;
+    if !result.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&result.stderr).into_owned()));
+    }
+    fs::read_to_string(&output)
+}
+
+// The lines of push_composer's body, without the assembler's directives.
+pub fn function(code: &str, emit: Emit) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut inside = false;
+    for line in code.lines() {
+        match emit {
+            Emit::Asm => {
+                if line.starts_with(FUNCTION) && line.ends_with(':') {
+                    inside = true;
+                } else if inside && line.starts_with(".Lfunc_end") {
+                    break;
+                } else if inside && !line.trim_start().starts_with(".cfi") {
+                    lines.push(line);
+                }
+            }
+            Emit::LlvmIr => {
+                if line.starts_with("define") && line.contains(&format!("@{}(", FUNCTION)) {
+                    inside = true;
+                } else if inside && line == "}" {
+                    break;
+                } else if inside {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    lines
+}
+
+fn is_memcpy(line: &str) -> bool {
+    line.contains("memcpy")
+}
+
+fn is_birth_store(line: &str, emit: Emit) -> bool {
+    match emit {
+        Emit::Asm => line.contains(&format!("${},", BIRTH)),
+        Emit::LlvmIr => line.trim_start().starts_with(&format!("store i32 {},", BIRTH)),
+    }
+}
+
+fn is_stack_slot(line: &str, emit: Emit) -> bool {
+    match emit {
+        Emit::Asm => line.contains("(%rsp)") || line.contains("(%rbp)"),
+        Emit::LlvmIr => line.contains("= alloca"),
+    }
+}
+
+// Lines worth pointing at in the listing.
+pub fn is_notable(line: &str, emit: Emit) -> bool {
+    is_memcpy(line) || is_birth_store(line, emit) || (emit == Emit::LlvmIr && is_stack_slot(line, emit))
+}
+
+pub fn judge(body: &[&str], emit: Emit) -> Verdict {
+    if body.iter().any(|line| is_memcpy(line)) {
+        return Verdict::Memcpy;
+    }
+    let stores: Vec<&&str> = body.iter().filter(|line| is_birth_store(line, emit)).collect();
+    let via_stack = match emit {
+        Emit::Asm => stores.iter().any(|line| is_stack_slot(line, emit)),
+        Emit::LlvmIr => body.iter().any(|line| is_stack_slot(line, emit)),
+    };
+    if stores.is_empty() {
+        Verdict::NoMemcpy
+    } else if via_stack {
+        Verdict::ViaStack
+    } else {
+        Verdict::InPlace
+    }
+}
+
+pub fn print(emit: Emit, dir: &Path) -> io::Result<Verdict> {
+    let code = compile(emit, dir)?;
+    let body = function(&code, emit);
+    if body.is_empty() {
+        return Err(io::Error::other(format!("no {} in the compiler's output", FUNCTION)));
+    }
+    println!("{}", SNIPPET);
+    println!("compiled with opt-level=3; lines marked >> are the ones the verdict rests on:");
+    for line in &body {
+        let marker = if is_notable(line, emit) { ">>" } else { "  " };
+        println!("{} {}", marker, line.replace('\t', "    "));
+    }
+    let verdict = judge(&body, emit);
+    println!();
+    println!("Person {}", verdict);
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn judges_assembly() {
+        let asm = "push_composer:\n\t.cfi_startproc\n\tmovq\t%rbx, (%rax,%rcx)\n\tmovl\t$1525, 24(%rax,%rcx)\n\tretq\n.Lfunc_end3:\n";
+        let body = function(asm, Emit::Asm);
+        assert_eq!(body, ["\tmovq\t%rbx, (%rax,%rcx)", "\tmovl\t$1525, 24(%rax,%rcx)", "\tretq"]);
+        assert_eq!(judge(&body, Emit::Asm), Verdict::InPlace);
+        assert_eq!(judge(&["\tmovl\t$1525, 24(%rsp)", "\tmovups\t(%rsp), %xmm0"], Emit::Asm), Verdict::ViaStack);
+        assert_eq!(judge(&["\tmovl\t$1525, 24(%rsp)", "\tcallq\t*memcpy@GOTPCREL(%rip)"], Emit::Asm), Verdict::Memcpy);
+        assert_eq!(judge(&["\tmov\tw8, #1525"], Emit::Asm), Verdict::NoMemcpy);
+    }
+
+    #[test]
+    fn judges_llvm_ir() {
+        let ir = "define void @push_composer(ptr %composers) {\nstart:\n  store i32 1525, ptr %end.i, align 8\n  ret void\n}\n";
+        let body = function(ir, Emit::LlvmIr);
+        assert_eq!(judge(&body, Emit::LlvmIr), Verdict::InPlace);
+        assert_eq!(judge(&["  %p = alloca [32 x i8], align 8", "  store i32 1525, ptr %p, align 8"], Emit::LlvmIr), Verdict::ViaStack);
+        assert_eq!(judge(&["  call void @llvm.memcpy.p0.p0.i64(ptr %d, ptr %p, i64 32, i1 false)"], Emit::LlvmIr), Verdict::Memcpy);
+    }
+}
@@ -0,0 +1,164 @@
+// Reading and writing composer lists as CSV, so they can live in a spreadsheet instead of in a row of
+// composers.push(...) lines. The file starts with a name,birth header. Fields may be quoted, with ""
+// for a quote inside a quoted field, and a quoted field may run over several lines. A composer whose
+// name has been taken is written with an empty name, and an empty name reads back as taken.
+
+use std::error;
+use std::fmt;
+
+use super::{ComposerRegistry, Person};
+
+const HEADER: [&str; 2] = ["name", "birth"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvError {
+    Empty,
+    BadHeader { found: Vec<String> },
+    FieldCount { line: usize, found: usize },
+    BadBirth { line: usize, value: String },
+    UnterminatedQuote { line: usize },
+    // A second row for a name the file has already listed.
+    DuplicateName { line: usize, name: String },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvError::Empty => write!(f, "no header line; expected name,birth"),
+            CsvError::BadHeader { found } => write!(f, "line 1: expected header name,birth, found {}", found.join(",")),
+            CsvError::FieldCount { line, found } => write!(f, "line {}: expected 2 fields, found {}", line, found),
+            CsvError::BadBirth { line, value } => write!(f, "line {}: birth year {:?} is not a whole number", line, value),
+            CsvError::UnterminatedQuote { line } => write!(f, "line {}: quoted field is never closed", line),
+            CsvError::DuplicateName { line, name } => write!(f, "line {}: {:?} is already listed", line, name),
+        }
+    }
+}
+
+impl error::Error for CsvError {}
+
+// Splits the text into records, each with the line number it starts on.
+fn records(text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut fields)));
+                line += 1;
+                start = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(CsvError::UnterminatedQuote { line: start });
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start, fields));
+    }
+    // Blank lines carry no record.
+    records.retain(|(_, fields)| !(fields.len() == 1 && fields[0].trim().is_empty()));
+    Ok(records)
+}
+
+pub fn read(text: &str) -> Result<ComposerRegistry, CsvError> {
+    let mut records = records(text)?.into_iter();
+    let (_, header) = records.next().ok_or(CsvError::Empty)?;
+    if header.len() != HEADER.len() || header.iter().zip(HEADER.iter()).any(|(found, expected)| found.trim() != *expected) {
+        return Err(CsvError::BadHeader { found: header });
+    }
+
+    let mut registry = ComposerRegistry::new();
+    for (line, fields) in records {
+        if fields.len() != 2 {
+            return Err(CsvError::FieldCount { line, found: fields.len() });
+        }
+        let mut fields = fields.into_iter();
+        let name = fields.next().unwrap();
+        let birth = fields.next().unwrap();
+        let birth = birth.trim().parse().map_err(|_| CsvError::BadBirth { line, value: birth })?;
+        if !name.is_empty() && registry.get(&name).is_some() {
+            return Err(CsvError::DuplicateName { line, name });
+        }
+        let name = if name.is_empty() { None } else { Some(name) };
+        registry.insert(Person { name, birth });
+    }
+    Ok(registry)
+}
+
+fn quote(field: &str) -> String {
+    let needs_quotes = field.contains([',', '"', '\n', '\r'])
+        || field.starts_with(' ') || field.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn write(registry: &ComposerRegistry) -> String {
+    let mut text = HEADER.join(",");
+    text.push('\n');
+    for composer in registry {
+        text.push_str(&format!("{},{}\n", quote(composer.name().unwrap_or("")), composer.birth));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut registry = ComposerRegistry::new();
+        registry.insert(Person::new("Palestrina".to_string(), 1525));
+        registry.insert(Person::new("Bach, Johann Sebastian".to_string(), 1685));
+        registry.insert(Person::new("Josquin \"des Prez\"".to_string(), 1450));
+        registry.insert(Person::new("Line\nBreak".to_string(), 1600));
+        registry.insert(Person { name: None, birth: 1700 });
+
+        let text = write(&registry);
+        let read_back = read(&text).unwrap();
+        assert_eq!(read_back.into_sorted(), registry.into_sorted());
+    }
+
+    #[test]
+    fn quoted_fields() {
+        let text = "name,birth\r\n\"Bach, J. S.\",1685\r\n\"Josquin \"\"des Prez\"\"\", 1450\r\n\r\n";
+        let names: Vec<String> = read(text).unwrap().into_iter().filter_map(|c| c.name).collect();
+        assert_eq!(names, ["Josquin \"des Prez\"", "Bach, J. S."]);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        assert_eq!(read("").unwrap_err(), CsvError::Empty);
+        assert_eq!(read("name,born\n").unwrap_err(), CsvError::BadHeader { found: vec!["name".to_string(), "born".to_string()] });
+        assert_eq!(read("name,birth\nPalestrina,1525\n\"Byrd\nWilliam\",1540\nDowland,c. 1563\n").unwrap_err(),
+                   CsvError::BadBirth { line: 5, value: "c. 1563".to_string() });
+        assert_eq!(read("name,birth\nPalestrina\n").unwrap_err(), CsvError::FieldCount { line: 2, found: 1 });
+        assert_eq!(read("name,birth\n\"Palestrina,1525\n").unwrap_err(), CsvError::UnterminatedQuote { line: 2 });
+        assert_eq!(read("name,birth\nPalestrina,1525\n,1600\n,1700\nPalestrina,1526\n").unwrap_err(),
+                   CsvError::DuplicateName { line: 5, name: "Palestrina".to_string() });
+    }
+}
@@ -0,0 +1,435 @@
+// JSON encoding for composers, for exchanging them with other tools. A Person is an object
+//     {"name": "Palestrina", "birth": 1525}
+// where the name is null once it has been taken, and a collection of composers is an array of those.
+// When decoding goes wrong the error names the JSON path of the offending field, like $[2].birth.
+
+use std::error;
+use std::fmt;
+
+use super::{ComposerRegistry, Person};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // Keeps the keys in the order they were written.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+// Compact, except that the elements of a top-level array go on lines of their own.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) if f.alternate() => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    f.write_str(if i == 0 { "\n  " } else { ",\n  " })?;
+                    write!(f, "{}", item)?;
+                }
+                f.write_str(if items.is_empty() { "]" } else { "\n]" })
+            }
+            Value::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Value::Object(members) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ": {}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonError {
+    // The text isn't JSON at all. offset is in bytes.
+    Syntax { offset: usize, message: String },
+    // The text is JSON, but the value at path isn't what a composer needs there.
+    Field { path: String, message: String },
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonError::Syntax { offset, message } => write!(f, "at byte {}: {}", offset, message),
+            JsonError::Field { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl error::Error for JsonError {}
+
+fn field_error(path: &str, message: String) -> JsonError {
+    JsonError::Field { path: path.to_string(), message }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+pub trait FromJson: Sized {
+    // path is where value was found, for error messages.
+    fn from_json(value: &Value, path: &str) -> Result<Self, JsonError>;
+}
+
+pub fn encode<T: ToJson>(value: &T) -> String {
+    format!("{:#}", value.to_json())
+}
+
+pub fn decode<T: FromJson>(text: &str) -> Result<T, JsonError> {
+    T::from_json(&parse(text)?, "$")
+}
+
+fn birth_from_json(value: &Value, path: &str) -> Result<i32, JsonError> {
+    let path = format!("{}.birth", path);
+    match value.get("birth") {
+        Some(&Value::Number(n)) if n.fract() == 0.0 && n >= i32::MIN as f64 && n <= i32::MAX as f64 => Ok(n as i32),
+        Some(Value::Number(n)) => Err(field_error(&path, format!("expected a whole year, found {}", n))),
+        Some(other) => Err(field_error(&path, format!("expected a number, found {}", other.kind()))),
+        None => Err(field_error(&path, "missing".to_string())),
+    }
+}
+
+fn name_from_json(value: &Value, path: &str) -> Result<Option<String>, JsonError> {
+    let path = format!("{}.name", path);
+    match value.get("name") {
+        Some(Value::String(name)) => Ok(Some(name.clone())),
+        Some(Value::Null) => Ok(None),
+        Some(other) => Err(field_error(&path, format!("expected a string, found {}", other.kind()))),
+        None => Err(field_error(&path, "missing".to_string())),
+    }
+}
+
+fn check_object(value: &Value, path: &str) -> Result<(), JsonError> {
+    match value {
+        Value::Object(_) => Ok(()),
+        other => Err(field_error(path, format!("expected an object, found {}", other.kind()))),
+    }
+}
+
+impl ToJson for Person {
+    fn to_json(&self) -> Value {
+        let name = match self.name() {
+            Some(name) => Value::String(name.to_string()),
+            None => Value::Null,
+        };
+        Value::Object(vec![
+            ("name".to_string(), name),
+            ("birth".to_string(), Value::Number(self.birth as f64)),
+        ])
+    }
+}
+
+impl FromJson for Person {
+    fn from_json(value: &Value, path: &str) -> Result<Person, JsonError> {
+        check_object(value, path)?;
+        Ok(Person { name: name_from_json(value, path)?, birth: birth_from_json(value, path)? })
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(|item| item.to_json()).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value, path: &str) -> Result<Vec<T>, JsonError> {
+        match value {
+            Value::Array(items) => items.iter()
+                .enumerate()
+                .map(|(i, item)| T::from_json(item, &format!("{}[{}]", path, i)))
+                .collect(),
+            other => Err(field_error(path, format!("expected an array, found {}", other.kind()))),
+        }
+    }
+}
+
+impl ToJson for ComposerRegistry {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(|person| person.to_json()).collect())
+    }
+}
+
+impl FromJson for ComposerRegistry {
+    fn from_json(value: &Value, path: &str) -> Result<ComposerRegistry, JsonError> {
+        let mut registry = ComposerRegistry::new();
+        for (i, person) in Vec::<Person>::from_json(value, path)?.into_iter().enumerate() {
+            if let Some(name) = person.name().filter(|name| registry.get(name).is_some()) {
+                return Err(field_error(&format!("{}[{}].name", path, i), format!("{:?} is already listed", name)));
+            }
+            registry.insert(person);
+        }
+        Ok(registry)
+    }
+}
+
+pub fn parse(text: &str) -> Result<Value, JsonError> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos != text.len() {
+        return Err(parser.error("unexpected text after the value"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::Syntax { offset: self.pos, message: message.to_string() }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => {
+                for (word, value) in [("null", Value::Null), ("true", Value::Bool(true)), ("false", Value::Bool(false))] {
+                    if self.rest().starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, JsonError> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Value::Object(members));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, JsonError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += escape.len_utf8();
+                    match escape {
+                        '"' | '\\' | '/' => s.push(escape),
+                        'n' => s.push('\n'),
+                        'r' => s.push('\r'),
+                        't' => s.push('\t'),
+                        'b' => s.push('\u{8}'),
+                        'f' => s.push('\u{c}'),
+                        'u' => {
+                            let mut code = self.hex4()?;
+                            // A character outside the Basic Multilingual Plane is escaped as a UTF-16
+                            // surrogate pair, \uD8xx\uDCxx, which only makes a character together.
+                            if (0xD800..0xDC00).contains(&code) && self.rest().starts_with("\\u") {
+                                let start = self.pos;
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if (0xDC00..0xE000).contains(&low) {
+                                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                                } else {
+                                    self.pos = start;
+                                }
+                            }
+                            s.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        }
+                        _ => return Err(self.error("unknown escape")),
+                    }
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    // The four hex digits of a \u escape.
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let hex = self.rest().get(..4).ok_or_else(|| self.error("short \\u escape"))?;
+        let code = u32::from_str_radix(hex, 16).map_err(|_| self.error("bad \\u escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c))).unwrap_or(rest.len());
+        let n = rest[..len].parse().map_err(|_| self.error("bad number"))?;
+        self.pos += len;
+        Ok(Value::Number(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn people_round_trip() {
+        let people = vec![
+            Person::new("Palestrina".to_string(), 1525),
+            Person::new("Josquin \"des Prez\"".to_string(), 1450),
+        ];
+        assert_eq!(decode::<Vec<Person>>(&encode(&people)).unwrap(), people);
+
+        let taken = Person { name: None, birth: 1525 };
+        assert_eq!(encode(&taken), r#"{"name": null, "birth": 1525}"#);
+        assert_eq!(decode::<Person>(&encode(&taken)).unwrap(), taken);
+    }
+
+    #[test]
+    fn errors_name_the_path() {
+        let text = r#"[{"name": "Palestrina", "birth": 1525}, {"name": "Dowland", "birth": "1563"}]"#;
+        assert_eq!(decode::<ComposerRegistry>(text).unwrap_err(), JsonError::Field {
+            path: "$[1].birth".to_string(),
+            message: "expected a number, found a string".to_string(),
+        });
+        assert_eq!(decode::<Vec<Person>>(r#"[{"name": 1525, "birth": 1525}]"#).unwrap_err(), JsonError::Field {
+            path: "$[0].name".to_string(),
+            message: "expected a string, found a number".to_string(),
+        });
+        assert!(matches!(decode::<Vec<Person>>("[{\"name\": }]"), Err(JsonError::Syntax { offset: 10, .. })));
+        let twice = r#"[{"name": "Palestrina", "birth": 1525}, {"name": null, "birth": 1600}, {"name": "Palestrina", "birth": 1526}]"#;
+        assert_eq!(decode::<ComposerRegistry>(twice).unwrap_err(), JsonError::Field {
+            path: "$[2].name".to_string(),
+            message: "\"Palestrina\" is already listed".to_string(),
+        });
+    }
+
+    #[test]
+    fn surrogate_pairs_make_one_character() {
+        assert_eq!(parse(r#""\ud834\udd1e clef""#).unwrap(), Value::String("\u{1d11e} clef".to_string()));
+        assert_eq!(parse(r#""\u00e9\ud834 \udd1e""#).unwrap(), Value::String("é\u{fffd} \u{fffd}".to_string()));
+        assert_eq!(parse(r#""\ud834\u0041""#).unwrap(), Value::String("\u{fffd}A".to_string()));
+    }
+}
@@ -0,0 +1,229 @@
+// The Person/composers example from More Operations That Move, grown into a registry. Its API moves
+// values rather than cloning them: insert takes the Person by value, remove hands the owned Person back
+// to the caller, and into_sorted consumes the registry and gives up every Person it held.
+//
+// The chapter defines Person twice, the second time with an Option<String> name so that the name can
+// be taken out of a Person inside a vector. This Person is both: the name is an Option, and name(),
+// take_name() and restore_name() cover the usual uses without unwrapping it by hand.
+
+pub mod csv;
+pub mod json;
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Person { pub name: Option<String>, pub birth: i32 }
+
+// take_name() on a Person whose name has already been taken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTaken {
+    pub birth: i32,
+}
+
+impl fmt::Display for NameTaken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the name of the composer born in {} has already been taken", self.birth)
+    }
+}
+
+impl error::Error for NameTaken {}
+
+impl Person {
+    pub fn new(name: String, birth: i32) -> Person {
+        Person { name: Some(name), birth }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // Moves the name out to the caller, leaving None behind.
+    pub fn take_name(&mut self) -> Result<String, NameTaken> {
+        self.name.take().ok_or(NameTaken { birth: self.birth })
+    }
+
+    // Moves a name back in. If the Person still had one, it is handed back.
+    pub fn restore_name(&mut self, name: String) -> Option<String> {
+        self.name.replace(name)
+    }
+
+    // 1525 is in the 16th century, 1600 is still the 16th, 1601 is the 17th.
+    pub fn century(&self) -> i32 {
+        if self.birth > 0 {
+            (self.birth - 1) / 100 + 1
+        } else {
+            self.birth / 100 - 1
+        }
+    }
+}
+
+impl fmt::Display for Person {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, born {}", self.name().unwrap_or("(name taken)"), self.birth)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ComposerRegistry {
+    // Kept sorted by birth; composers born in the same year stay in the order they were inserted.
+    composers: Vec<Person>,
+}
+
+impl ComposerRegistry {
+    pub fn new() -> ComposerRegistry {
+        ComposerRegistry::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.composers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.composers.is_empty()
+    }
+
+    // Takes ownership of person. If a composer with the same name was already registered, they are
+    // replaced and moved back out to the caller. Composers whose name was taken never replace anyone.
+    pub fn insert(&mut self, person: Person) -> Option<Person> {
+        let replaced = person.name().and_then(|name| self.remove(name));
+        let index = self.composers.partition_point(|c| c.birth <= person.birth);
+        self.composers.insert(index, person);
+        replaced
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Person> {
+        self.composers.iter().find(|c| c.name() == Some(name))
+    }
+
+    // Moves the Person out of the registry, leaving nothing behind.
+    pub fn remove(&mut self, name: &str) -> Option<Person> {
+        let index = self.composers.iter().position(|c| c.name() == Some(name))?;
+        Some(self.composers.remove(index))
+    }
+
+    // Oldest first.
+    pub fn iter(&self) -> std::slice::Iter<'_, Person> {
+        self.composers.iter()
+    }
+
+    pub fn by_century(&self) -> BTreeMap<i32, Vec<&Person>> {
+        let mut centuries: BTreeMap<i32, Vec<&Person>> = BTreeMap::new();
+        for composer in &self.composers {
+            centuries.entry(composer.century()).or_default().push(composer);
+        }
+        centuries
+    }
+
+    // Consumes the registry, moving every Person out to the caller, oldest first.
+    pub fn into_sorted(self) -> Vec<Person> {
+        self.composers
+    }
+}
+
+impl<'a> IntoIterator for &'a ComposerRegistry {
+    type Item = &'a Person;
+    type IntoIter = std::slice::Iter<'a, Person>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for ComposerRegistry {
+    type Item = Person;
+    type IntoIter = std::vec::IntoIter<Person>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.composers.into_iter()
+    }
+}
+
+impl Extend<Person> for ComposerRegistry {
+    fn extend<I: IntoIterator<Item = Person>>(&mut self, people: I) {
+        for person in people {
+            self.insert(person);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_and_restore_name() {
+        let mut palestrina = Person::new("Palestrina".to_string(), 1525);
+        assert_eq!(palestrina.take_name(), Ok("Palestrina".to_string()));
+        assert_eq!(palestrina.name(), None);
+        assert_eq!(palestrina.take_name(), Err(NameTaken { birth: 1525 }));
+        assert_eq!(palestrina.restore_name("Palestrina".to_string()), None);
+        assert_eq!(palestrina.name(), Some("Palestrina"));
+        assert_eq!(palestrina.restore_name("Giovanni".to_string()), Some("Palestrina".to_string()));
+    }
+
+    fn names(registry: &ComposerRegistry) -> Vec<&str> {
+        registry.iter().map(|c| c.name().unwrap_or("")).collect()
+    }
+
+    #[test]
+    fn insert_keeps_births_in_order() {
+        let mut registry = ComposerRegistry::new();
+        registry.extend(vec![
+            Person::new("Monteverdi".to_string(), 1567),
+            Person::new("Palestrina".to_string(), 1525),
+            Person::new("Dowland".to_string(), 1563),
+            Person::new("Gesualdo".to_string(), 1566),
+            Person::new("Marenzio".to_string(), 1553),
+            Person::new("Caccini".to_string(), 1551),
+            Person::new("Peri".to_string(), 1561),
+            Person::new("Sweelinck".to_string(), 1562),
+            Person::new("Bull".to_string(), 1562),
+        ]);
+        // Sweelinck and Bull were both born in 1562, and keep the order they were inserted in.
+        assert_eq!(names(&registry), [
+            "Palestrina", "Caccini", "Marenzio", "Peri", "Sweelinck", "Bull", "Dowland", "Gesualdo", "Monteverdi",
+        ]);
+    }
+
+    #[test]
+    fn insert_replaces_and_remove_moves_out() {
+        let mut registry = ComposerRegistry::new();
+        assert_eq!(registry.insert(Person::new("Dowland".to_string(), 1562)), None);
+        assert_eq!(registry.insert(Person { name: None, birth: 1600 }), None);
+        assert_eq!(registry.insert(Person { name: None, birth: 1600 }), None);
+        assert_eq!(registry.insert(Person::new("Dowland".to_string(), 1563)), Some(Person::new("Dowland".to_string(), 1562)));
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.get("Dowland").map(|c| c.birth), Some(1563));
+
+        assert_eq!(registry.remove("Dowland"), Some(Person::new("Dowland".to_string(), 1563)));
+        assert_eq!(registry.remove("Dowland"), None);
+        assert_eq!(registry.get("Dowland"), None);
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn centuries() {
+        let century = |birth| Person { name: None, birth }.century();
+        assert_eq!([1, 100, 101, 1525, 1600, 1601, 2000, 2001].map(century), [1, 1, 2, 16, 16, 17, 20, 21]);
+        // There's no year 0 in the calendar; here it's 1 BC, in the 1st century BC.
+        assert_eq!([0, -99, -100, -101].map(century), [-1, -1, -2, -2]);
+
+        let mut registry = ComposerRegistry::new();
+        registry.extend(vec![
+            Person::new("Lully".to_string(), 1632),
+            Person::new("Palestrina".to_string(), 1525),
+            Person::new("Monteverdi".to_string(), 1567),
+            Person::new("Schütz".to_string(), 1585),
+            Person::new("Purcell".to_string(), 1659),
+        ]);
+        let by_century: Vec<(i32, Vec<&str>)> = registry.by_century().into_iter()
+            .map(|(century, composers)| (century, composers.iter().filter_map(|c| c.name()).collect()))
+            .collect();
+        assert_eq!(by_century, [
+            (16, vec!["Palestrina", "Monteverdi", "Schütz"]),
+            (17, vec!["Lully", "Purcell"]),
+        ]);
+    }
+}
@@ -0,0 +1,96 @@
+// A global allocator that counts what passes through it, for checking the chapter's claims about
+// which operations touch the heap. It is only installed when the crate is built with
+//     cargo run --features count-allocations
+// and otherwise every measurement comes back as zero, which is what enabled() is for.
+//
+// The counters are process-wide, so a measurement also picks up anything other threads allocate
+// while it runs. The lessons are single-threaded, so that doesn't come up.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static BYTES_FREED: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg_attr(not(feature = "count-allocations"), allow(dead_code))]
+pub struct Counting;
+
+// realloc is left to the default, which goes through alloc and dealloc and so counts as one of each.
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES_ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES_FREED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+pub fn enabled() -> bool {
+    cfg!(feature = "count-allocations")
+}
+
+// The counters at one moment. Subtract two snapshots to see what happened in between.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub allocations: usize,
+    pub deallocations: usize,
+    pub bytes_allocated: usize,
+    pub bytes_freed: usize,
+}
+
+impl Counts {
+    pub fn live_bytes(&self) -> isize {
+        self.bytes_allocated as isize - self.bytes_freed as isize
+    }
+}
+
+impl std::ops::Sub for Counts {
+    type Output = Counts;
+
+    fn sub(self, earlier: Counts) -> Counts {
+        Counts {
+            allocations: self.allocations - earlier.allocations,
+            deallocations: self.deallocations - earlier.deallocations,
+            bytes_allocated: self.bytes_allocated - earlier.bytes_allocated,
+            bytes_freed: self.bytes_freed - earlier.bytes_freed,
+        }
+    }
+}
+
+impl std::fmt::Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} allocations, {} deallocations, {:+} live bytes",
+               self.allocations, self.deallocations, self.live_bytes())
+    }
+}
+
+pub fn snapshot() -> Counts {
+    Counts {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        bytes_allocated: BYTES_ALLOCATED.load(Ordering::Relaxed),
+        bytes_freed: BYTES_FREED.load(Ordering::Relaxed),
+    }
+}
+
+// Runs f and returns its result along with what it did to the heap. Whatever f returns is still
+// live, so it counts towards the live bytes.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Counts) {
+    let before = snapshot();
+    let result = f();
+    (result, snapshot() - before)
+}
@@ -0,0 +1,195 @@
+// A model of C++ value semantics for std::vector<std::string>, enough to run the chapter's
+//     std::vector<std::string> s = {"udon", "ramen", "soba"};
+//     std::vector<std::string> t = s;
+//     std::vector<std::string> u = s;
+// Copying a vector allocates a new element buffer and copies every string into it, and copying a
+// string allocates a new text buffer, so each assignment is a deep copy. The model keeps track of every
+// heap block so the "3 lists, with 9 total values" can be counted instead of asserted.
+
+use std::fmt;
+
+// Sizes as in libstdc++ on a 64-bit target.
+pub const VECTOR_HEADER: usize = 24;
+pub const STRING_HEADER: usize = 32;
+// libstdc++ keeps strings of up to 15 bytes inside the header itself. The book's diagrams leave this
+// out, so it is off unless asked for.
+pub const SSO_CAPACITY: usize = 15;
+
+pub type BlockId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub bytes: usize,
+    pub what: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CppString {
+    pub text: String,
+    // None when the text fits in the header.
+    pub buffer: Option<BlockId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CppVector {
+    pub buffer: BlockId,
+    pub elements: Vec<CppString>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub allocations: usize,
+    pub bytes_allocated: usize,
+}
+
+#[derive(Default)]
+pub struct Machine {
+    pub small_string_optimization: bool,
+    heap: Vec<Option<Block>>,
+    variables: Vec<(String, CppVector)>,
+    stats: Stats,
+}
+
+impl Machine {
+    pub fn new() -> Machine {
+        Machine::default()
+    }
+
+    pub fn with_small_string_optimization() -> Machine {
+        Machine { small_string_optimization: true, ..Machine::default() }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn live_bytes(&self) -> usize {
+        self.heap.iter().flatten().map(|block| block.bytes).sum()
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&CppVector> {
+        self.variables.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    // std::vector<std::string> name = {texts...};
+    pub fn construct(&mut self, name: &str, texts: &[&str]) {
+        let elements = texts.iter().map(|text| self.new_string(text)).collect();
+        let vector = self.new_vector(elements);
+        self.bind(name, vector);
+    }
+
+    // std::vector<std::string> dest = src; (or dest = src; when dest already exists)
+    pub fn copy(&mut self, dest: &str, src: &str) {
+        let source = self.variable(src).unwrap_or_else(|| panic!("no variable {}", src)).clone();
+        let elements = source.elements.iter().map(|s| self.new_string(&s.text)).collect();
+        let vector = self.new_vector(elements);
+        self.bind(dest, vector);
+    }
+
+    fn bind(&mut self, name: &str, vector: CppVector) {
+        match self.variables.iter().position(|(n, _)| n == name) {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.variables[index].1, vector);
+                self.destroy(old);
+            }
+            None => self.variables.push((name.to_string(), vector)),
+        }
+    }
+
+    fn destroy(&mut self, vector: CppVector) {
+        for element in vector.elements {
+            if let Some(block) = element.buffer {
+                self.heap[block] = None;
+            }
+        }
+        self.heap[vector.buffer] = None;
+    }
+
+    fn new_string(&mut self, text: &str) -> CppString {
+        let buffer = if self.small_string_optimization && text.len() <= SSO_CAPACITY {
+            None
+        } else {
+            // Room for the terminating NUL.
+            Some(self.alloc(text.len() + 1, format!("text {:?}", text)))
+        };
+        CppString { text: text.to_string(), buffer }
+    }
+
+    fn new_vector(&mut self, elements: Vec<CppString>) -> CppVector {
+        let buffer = self.alloc(elements.len() * STRING_HEADER, format!("buffer of {} std::string", elements.len()));
+        CppVector { buffer, elements }
+    }
+
+    fn alloc(&mut self, bytes: usize, what: String) -> BlockId {
+        self.stats.allocations += 1;
+        self.stats.bytes_allocated += bytes;
+        self.heap.push(Some(Block { bytes, what }));
+        self.heap.len() - 1
+    }
+
+    fn block(&self, id: BlockId) -> &Block {
+        self.heap[id].as_ref().expect("block already freed")
+    }
+}
+
+// The object graph: every variable's header on the stack and the heap blocks hanging off it.
+impl fmt::Display for Machine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, vector) in &self.variables {
+            let buffer = self.block(vector.buffer);
+            writeln!(f, "    {} ({}-byte header) -> block #{}, {} bytes: {}",
+                     name, VECTOR_HEADER, vector.buffer, buffer.bytes, buffer.what)?;
+            for (i, element) in vector.elements.iter().enumerate() {
+                match element.buffer {
+                    Some(id) => {
+                        let block = self.block(id);
+                        writeln!(f, "        [{}] -> block #{}, {} bytes: {}", i, id, block.bytes, block.what)?;
+                    }
+                    None => writeln!(f, "        [{}] {:?} stored inline", i, element.text)?,
+                }
+            }
+        }
+        writeln!(f, "    {} allocations, {} bytes allocated, {} bytes live",
+                 self.stats.allocations, self.stats.bytes_allocated, self.live_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noodles(mut machine: Machine) -> Machine {
+        machine.construct("s", &["udon", "ramen", "soba"]);
+        machine.copy("t", "s");
+        machine.copy("u", "s");
+        machine
+    }
+
+    #[test]
+    fn each_copy_triples_the_allocations_and_bytes() {
+        let mut one = Machine::new();
+        one.construct("s", &["udon", "ramen", "soba"]);
+        let machine = noodles(Machine::new());
+        assert_eq!(machine.stats().allocations, 12);
+        assert_eq!(machine.stats().bytes_allocated, 3 * one.stats().bytes_allocated);
+        assert_eq!(machine.live_bytes(), machine.stats().bytes_allocated);
+    }
+
+    #[test]
+    fn short_strings_stay_in_the_header() {
+        let machine = noodles(Machine::with_small_string_optimization());
+        assert_eq!(machine.stats().allocations, 3);
+        assert!(machine.variable("u").unwrap().elements.iter().all(|s| s.buffer.is_none()));
+    }
+
+    #[test]
+    fn copying_onto_an_existing_name_frees_its_old_blocks() {
+        let mut machine = Machine::new();
+        machine.construct("s", &["udon", "ramen", "soba"]);
+        let one_list = machine.live_bytes();
+        machine.construct("t", &["a much longer string than any noodle"]);
+        machine.copy("t", "s");
+        assert_eq!(machine.live_bytes(), 2 * one_list);
+        assert_eq!(machine.stats().allocations, 4 + 2 + 4);
+    }
+}
@@ -0,0 +1,301 @@
+// Ownership diagrams, drawn from real values instead of the book's "see page N" figures. A diagram is
+// a set of boxes, each either a variable on the stack or a block on the heap, made of cells; a cell
+// that holds a pointer names the box it points to. The same diagram can be printed as text for the
+// terminal or written out as Graphviz DOT (and SVG, if Graphviz is installed).
+
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
+
+pub struct Cell {
+    pub text: String,
+    pub target: Option<String>,
+}
+
+impl Cell {
+    pub fn text(text: impl Into<String>) -> Cell {
+        Cell { text: text.into(), target: None }
+    }
+
+    pub fn pointer(target: &str) -> Cell {
+        Cell { text: "ptr".to_string(), target: Some(target.to_string()) }
+    }
+}
+
+pub struct Node {
+    pub id: String,
+    pub on_stack: bool,
+    // Shown next to the id: the variable's name for stack boxes, the address for heap blocks.
+    pub label: String,
+    pub cells: Vec<Cell>,
+}
+
+pub struct Diagram {
+    pub title: String,
+    pub nodes: Vec<Node>,
+}
+
+// The id Diagram::heap gives the heap block it adds index'th, counting from 0.
+fn heap_id(index: usize) -> String {
+    format!("H{}", index)
+}
+
+impl Diagram {
+    pub fn new(title: &str) -> Diagram {
+        Diagram { title: title.to_string(), nodes: Vec::new() }
+    }
+
+    pub fn stack(&mut self, name: &str, cells: Vec<Cell>) {
+        self.nodes.push(Node { id: name.to_string(), on_stack: true, label: name.to_string(), cells });
+    }
+
+    // Heap blocks are named H0, H1, H2... in the order they're added, and labelled with their address.
+    pub fn heap(&mut self, address: usize, cells: Vec<Cell>) -> String {
+        let count = self.nodes.iter().filter(|node| !node.on_stack).count();
+        let id = heap_id(count);
+        self.nodes.push(Node { id: id.clone(), on_stack: false, label: format!("{:#x}", address), cells });
+        id
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str("digraph ownership {\n");
+        dot.push_str(&format!("    label=\"{}\";\n    rankdir=LR;\n    node [shape=record];\n", escape(&self.title)));
+        for (cluster, on_stack) in [("stack", true), ("heap", false)] {
+            dot.push_str(&format!("    subgraph cluster_{} {{\n        label=\"{}\";\n", cluster, cluster));
+            for node in self.nodes.iter().filter(|node| node.on_stack == on_stack) {
+                let fields: Vec<String> = node.cells.iter().enumerate()
+                    .map(|(i, cell)| format!("<f{}> {}", i, escape(&cell.text)))
+                    .collect();
+                dot.push_str(&format!("        \"{}\" [label=\"{}|{}\"];\n", node.id, escape(&node.label), fields.join("|")));
+            }
+            dot.push_str("    }\n");
+        }
+        for node in &self.nodes {
+            for (i, cell) in node.cells.iter().enumerate() {
+                if let Some(target) = &cell.target {
+                    dot.push_str(&format!("    \"{}\":f{} -> \"{}\";\n", node.id, i, target));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Writes <dir>/<name>.dot, then asks Graphviz for <name>.svg. Returns the files that were written;
+    // a missing dot command isn't an error, there's just no SVG.
+    pub fn write(&self, dir: &Path, name: &str) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        let dot_path = dir.join(format!("{}.dot", name));
+        fs::write(&dot_path, self.to_dot())?;
+        let mut written = vec![dot_path.clone()];
+
+        let svg_path = dir.join(format!("{}.svg", name));
+        let status = Command::new("dot").arg("-Tsvg").arg(&dot_path).arg("-o").arg(&svg_path).status();
+        if let Ok(status) = status {
+            if status.success() {
+                written.push(svg_path);
+            }
+        }
+        Ok(written)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "{}|<>\"\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl fmt::Display for Diagram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.title)?;
+        for (heading, on_stack) in [("stack:", true), ("heap:", false)] {
+            writeln!(f, "  {}", heading)?;
+            for node in self.nodes.iter().filter(|node| node.on_stack == on_stack) {
+                let cells: Vec<String> = node.cells.iter()
+                    .map(|cell| match &cell.target {
+                        Some(target) => format!("{} -> {}", cell.text, target),
+                        None => cell.text.clone(),
+                    })
+                    .collect();
+                let name = if on_stack { node.label.clone() } else { format!("{} @ {}", node.id, node.label) };
+                writeln!(f, "    {}: [{}]", name, cells.join(" | "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn string_header(s: &str, capacity: usize, target: &str) -> Vec<Cell> {
+    vec![Cell::pointer(target), Cell::text(format!("cap {}", capacity)), Cell::text(format!("len {}", s.len()))]
+}
+
+// Adds a String's text buffer to the heap and returns the String's header cells pointing at it.
+#[allow(clippy::ptr_arg)]
+fn string(diagram: &mut Diagram, s: &String) -> Vec<Cell> {
+    let text = diagram.heap(s.as_ptr() as usize, vec![Cell::text(format!("{:?}", s))]);
+    string_header(s, s.capacity(), &text)
+}
+
+// A Vec<String> owned by the variable name: its header on the stack, the element buffer, and the text
+// of each element.
+#[allow(clippy::ptr_arg)]
+pub fn vec_of_strings(title: &str, name: &str, v: &Vec<String>) -> Diagram {
+    let mut diagram = Diagram::new(title);
+    let mut elements = Vec::new();
+    for s in v {
+        elements.extend(string(&mut diagram, s));
+    }
+    let buffer = diagram.heap(v.as_ptr() as usize, elements);
+    diagram.stack(name, vec![
+        Cell::pointer(&buffer),
+        Cell::text(format!("cap {}", v.capacity())),
+        Cell::text(format!("len {}", v.len())),
+    ]);
+    diagram
+}
+
+// The String and i32 assignment from Copy Types: str1 has been moved out of, num1 still holds its value.
+#[allow(clippy::ptr_arg)]
+pub fn string_and_int(str2: &String, num1: i32, num2: i32) -> Diagram {
+    let mut diagram = Diagram::new("let str2 = str1; moves, let num2 = num1; copies");
+    diagram.stack("str1", vec![Cell::text("uninitialized")]);
+    let cells = string(&mut diagram, str2);
+    diagram.stack("str2", cells);
+    diagram.stack("num1", vec![Cell::text(num1.to_string())]);
+    diagram.stack("num2", vec![Cell::text(num2.to_string())]);
+    diagram
+}
+
+// Several Rc<String> pointers sharing one block, which holds the counts and the String's header.
+pub fn rc_strings(title: &str, pointers: &[(&str, &Rc<String>)]) -> Diagram {
+    let mut diagram = Diagram::new(title);
+    let (_, first) = pointers[0];
+    let text = diagram.heap(first.as_ptr() as usize, vec![Cell::text(format!("{:?}", first.as_str()))]);
+    let mut cells = vec![
+        Cell::text(format!("strong {}", Rc::strong_count(first))),
+        Cell::text(format!("weak {}", Rc::weak_count(first))),
+    ];
+    cells.extend(string_header(first, first.capacity(), &text));
+    let block = diagram.heap(Rc::as_ptr(first) as usize, cells);
+    for (name, _) in pointers {
+        diagram.stack(name, vec![Cell::pointer(&block)]);
+    }
+    diagram
+}
+
+pub struct Link {
+    pub name: &'static str,
+    pub next: RefCell<Option<Rc<Link>>>,
+}
+
+// Two Rc blocks pointing at each other. Each count includes the other block's pointer, so neither
+// can reach zero once a and b go out of scope.
+pub fn rc_cycle(a: &Rc<Link>, b: &Rc<Link>) -> Diagram {
+    let mut diagram = Diagram::new("an Rc cycle: a.next = b and b.next = a");
+    let ids = [heap_id(0), heap_id(1)];
+    for (link, next) in [(a, &ids[1]), (b, &ids[0])] {
+        let pointer = match &*link.next.borrow() {
+            Some(_) => Cell::pointer(next),
+            None => Cell::text("None"),
+        };
+        let cells = vec![
+            Cell::text(format!("strong {}", Rc::strong_count(link))),
+            Cell::text(link.name),
+            pointer,
+        ];
+        diagram.heap(Rc::as_ptr(link) as usize, cells);
+    }
+    diagram.stack("a", vec![Cell::pointer(&ids[0])]);
+    diagram.stack("b", vec![Cell::pointer(&ids[1])]);
+    diagram
+}
+
+// Builds the cycle with RefCell, draws it, and then breaks it again so the demonstration doesn't
+// actually leak.
+pub fn rc_cycle_example() -> Diagram {
+    let a = Rc::new(Link { name: "a", next: RefCell::new(None) });
+    let b = Rc::new(Link { name: "b", next: RefCell::new(Some(a.clone())) });
+    *a.next.borrow_mut() = Some(b.clone());
+    let diagram = rc_cycle(&a, &b);
+    *a.next.borrow_mut() = None;
+    diagram
+}
+
+// Every diagram, drawn from freshly built values, under the names the CLI uses for its files.
+pub fn all() -> Vec<(&'static str, Diagram)> {
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    let noodles = vec_of_strings("let s = vec![\"udon\", \"ramen\", \"soba\"] (to_string'd)", "s", &s);
+
+    let str1 = "somnambulance".to_string();
+    let str2 = str1;
+    let num1: i32 = 36;
+    let num2 = num1;
+    let copies = string_and_int(&str2, num1, num2);
+
+    let s: Rc<String> = Rc::new("shirataki".to_string());
+    let t = s.clone();
+    let u = s.clone();
+    let shared = rc_strings("three Rc<String> pointing at one block", &[("s", &s), ("t", &t), ("u", &u)]);
+
+    let cycle = rc_cycle_example();
+
+    vec![("noodles", noodles), ("string-and-int", copies), ("rc-strings", shared), ("rc-cycle", cycle)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_quotes_record_syntax() {
+        assert_eq!(escape("plain text"), "plain text");
+        assert_eq!(escape(r#"{"a"|<b>}\"#), r#"\{\"a\"\|\<b\>\}\\"#);
+    }
+
+    #[test]
+    fn dot_has_a_cluster_each_and_an_edge_per_pointer() {
+        let mut diagram = Diagram::new("t = \"s\"");
+        let text = diagram.heap(0x1000, vec![Cell::text("u|d|o|n")]);
+        let buffer = diagram.heap(0x2000, vec![Cell::pointer(&text), Cell::text("cap 4")]);
+        diagram.stack("t", vec![Cell::pointer(&buffer), Cell::text("len 1")]);
+        assert_eq!(diagram.to_dot(), "\
+digraph ownership {
+    label=\"t = \\\"s\\\"\";
+    rankdir=LR;
+    node [shape=record];
+    subgraph cluster_stack {
+        label=\"stack\";
+        \"t\" [label=\"t|<f0> ptr|<f1> len 1\"];
+    }
+    subgraph cluster_heap {
+        label=\"heap\";
+        \"H0\" [label=\"0x1000|<f0> u\\|d\\|o\\|n\"];
+        \"H1\" [label=\"0x2000|<f0> ptr|<f1> cap 4\"];
+    }
+    \"H1\":f0 -> \"H0\";
+    \"t\":f0 -> \"H1\";
+}
+");
+    }
+
+    #[test]
+    fn heap_ids_stay_distinct_past_the_alphabet() {
+        let mut diagram = Diagram::new("many blocks");
+        let mut ids: Vec<String> = (0..300).map(|i| diagram.heap(i, Vec::new())).collect();
+        assert_eq!(ids[26], "H26");
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 300);
+    }
+}
@@ -0,0 +1,66 @@
+// Memory layout inspection for Vec<String>. A vector's value proper is a three-word header on the
+// stack (pointer, capacity, length); the elements live in a heap buffer the pointer refers to, and each
+// String element is itself a three-word header pointing at its own text buffer. Printing the addresses
+// before and after a move or a clone shows which of those parts actually got copied.
+
+use std::fmt;
+use std::mem;
+
+pub struct StringLayout {
+    pub header_at: usize,
+    pub buffer: usize,
+    pub capacity: usize,
+    pub len: usize,
+    pub text: String,
+}
+
+pub struct VecLayout {
+    pub header_at: usize,
+    // The header exactly as it sits in memory. The standard library doesn't promise which word is
+    // which, so the labelled fields below come from the Vec's own methods instead.
+    pub words: [usize; 3],
+    pub buffer: usize,
+    pub capacity: usize,
+    pub len: usize,
+    pub strings: Vec<StringLayout>,
+}
+
+// Takes &Vec rather than &[String] because the header is the thing being inspected.
+#[allow(clippy::ptr_arg)]
+pub fn inspect(v: &Vec<String>) -> VecLayout {
+    assert_eq!(mem::size_of::<Vec<String>>(), 3 * mem::size_of::<usize>());
+    // Safe: we only read the header's bytes as plain integers, and just checked there are three words.
+    let words: [usize; 3] = unsafe { mem::transmute_copy(v) };
+
+    let strings = v.iter()
+        .map(|s| StringLayout {
+            header_at: s as *const String as usize,
+            buffer: s.as_ptr() as usize,
+            capacity: s.capacity(),
+            len: s.len(),
+            text: s.clone(),
+        })
+        .collect();
+
+    VecLayout {
+        header_at: v as *const Vec<String> as usize,
+        words,
+        buffer: v.as_ptr() as usize,
+        capacity: v.capacity(),
+        len: v.len(),
+        strings,
+    }
+}
+
+impl fmt::Display for VecLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "header at {:#x}: words [{:#x}, {:#x}, {:#x}]",
+                 self.header_at, self.words[0], self.words[1], self.words[2])?;
+        writeln!(f, "  pointer {:#x}, capacity {}, length {}", self.buffer, self.capacity, self.len)?;
+        for (i, s) in self.strings.iter().enumerate() {
+            writeln!(f, "  [{}] header at {:#x} -> text {:#x} ({:?}, capacity {}, length {})",
+                     i, s.header_at, s.buffer, s.text, s.capacity, s.len)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+// Moves and Control Flow
+
+// The previous examples have very simple control flow. How do moves interact with more complicated code? The general principle is that, if it's possible for a variable to have had its value moved away, and it hasn't definitely been given a new value since, it's considered uninitialized.
+
+use crate::checker;
+use crate::trace::{self, MoveKind, Traced};
+
+/// The book leaves c, f, g, h and e undefined, and uses f and h with two signatures each. Here c is a
+/// plain bool, f, g and e take ownership of the vector, and h takes nothing and returns a fresh one.
+/// The vector is Traced and each helper records its move with #[track_caller], so the trace shows
+/// every move against the line of this file that made it, and the drop that follows in the callee.
+type Numbers = Traced<Vec<i32>>;
+
+/// Prints the step that just ran and what it left in x.
+fn step(statement: &str, x: String) {
+    println!("    {:<26} {}", statement, x);
+}
+
+fn holds(x: &Numbers) -> String {
+    format!("x holds #{} {:?}", x.id(), x.get())
+}
+
+const UNINITIALIZED: &str = "x is uninitialized";
+
+#[track_caller]
+fn f(x: Numbers) {
+    x.move_as(MoveKind::Argument, "f");
+}
+
+#[track_caller]
+fn g(x: Numbers) {
+    x.move_as(MoveKind::Argument, "g");
+}
+
+#[track_caller]
+fn h() -> Numbers {
+    Traced::new("h", vec![40, 50, 60]).move_as(MoveKind::Return, "x")
+}
+
+#[track_caller]
+fn e(x: Numbers) {
+    x.move_as(MoveKind::Argument, "e");
+}
+
+// For example, if a variable still has a value after evaluating an if expression's condition, then we can use it in both branches:
+fn branches(c: bool) {
+    let x = Traced::new("x", vec![10, 20, 30]);
+    step("let x = vec![10, 20, 30]", holds(&x));
+    if c {
+        f(x); //... ok to move from x here
+        step("f(x)", UNINITIALIZED.to_string());
+    } else {
+        g(x); // ... and ok to also move from x here
+        step("g(x)", UNINITIALIZED.to_string());
+    }
+    // h(x) // bad: x is uninitialized here if either path uses it
+}
+
+// For similar reasons, moving from a variable in a loop is forbidden:
+// let x = vec![10, 20, 30];
+// while f() {
+//     g(x); // bad: x would be moved in the first iteration,
+//           // uninitialized in second
+// }
+
+// That is, unless we've definitely given it a new value by the next iteration:
+/// The loop's condition counts down rounds, since f here takes the vector.
+fn reinitialize_in_loop(mut rounds: usize) {
+    let mut x = Traced::new("x", vec![10, 20, 30]);
+    step("let mut x = vec![...]", holds(&x));
+    while rounds > 0 {
+        g(x); // move from x
+        step("g(x)", UNINITIALIZED.to_string());
+        x = h(); // give x a fresh value
+        step("x = h()", holds(&x));
+        rounds -= 1;
+    }
+    e(x);
+    step("e(x)", UNINITIALIZED.to_string());
+}
+
+/// The examples again, as the checker in src/checker sees them, so the project can check the rule
+/// itself rather than leave it to rustc.
+const SNIPPETS: &[(&str, &str)] = &[
+    ("both branches move", "\
+let c = true;
+let x = vec![10, 20, 30];
+if c {
+    f(x);
+} else {
+    g(x);
+}
+"),
+    ("used after both branches move", "\
+let c = true;
+let x = vec![10, 20, 30];
+if c {
+    f(x);
+} else {
+    g(x);
+}
+h(x);
+"),
+    ("moved in a loop", "\
+let x = vec![10, 20, 30];
+while f() {
+    g(x);
+}
+"),
+    ("re-initialized in a loop", "\
+let mut x = vec![10, 20, 30];
+while f() {
+    g(x);
+    x = h();
+}
+e(x);
+"),
+];
+
+pub fn run() {
+    for c in [true, false] {
+        println!("if c {{ f(x) }} else {{ g(x) }} with c = {}:", c);
+        branches(c);
+        trace::print_timeline(&trace::take_log());
+    }
+    println!("while rounds > 0 {{ g(x); x = h(); }} e(x):");
+    reinitialize_in_loop(2);
+    trace::print_timeline(&trace::take_log());
+
+    println!("the same examples, checked by the rule above:");
+    checker::print_snippets(SNIPPETS);
+}
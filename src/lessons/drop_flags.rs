@@ -0,0 +1,104 @@
+// Drop Flags
+
+// Moves and Control Flow showed that Rust rejects any use of a variable whose value might have been moved away. Such a variable still has to be dropped at the end of its scope if it wasn't moved, though, and whether it was can depend on a condition that's only known at run time:
+// let x = vec![10, 20, 30];
+// if c {
+//     f(x);
+// }
+// ... x is dropped here, but only if c was false
+
+// For a variable like this the compiler keeps a hidden boolean alongside it, a drop flag. The flag is set when x is initialized, cleared when x is moved away, and consulted when x goes out of scope: x is dropped only if the flag is still set. A variable that is definitely moved, or definitely not, needs no flag, since the compiler knows which it is without one.
+
+use std::mem::ManuallyDrop;
+
+use crate::noisy::Noisy;
+use crate::trace;
+
+/// Prints a step and marks it in the trace log, so the log shows where each drop happened.
+fn step(text: &'static str) {
+    println!("    {}", text);
+    trace::statement(text);
+}
+
+fn f(x: Noisy) {
+    println!("    f took {}", x);
+} // x is dropped here, as f's parameter
+
+/// The example above, with a Noisy standing in for the vector.
+fn conditional_move(c: bool) {
+    let x = Noisy::new("x");
+    step("let x");
+    if c {
+        f(x);
+        step("f(x)");
+    }
+    step("scope exit");
+}
+
+/// The same function with the drop flag written out. ManuallyDrop stops Rust from dropping x by
+/// itself, and x_initialized is the flag, doing by hand what the compiler does for conditional_move.
+fn conditional_move_by_hand(c: bool) {
+    let mut x = ManuallyDrop::new(Noisy::new("x"));
+    let mut x_initialized = true;
+    step("let x; flag set");
+    if c {
+        x_initialized = false;
+        f(unsafe { ManuallyDrop::take(&mut x) }); // Safety: the flag is cleared first, and x is never read or dropped once it's clear.
+        step("f(x); flag cleared by the move");
+    }
+    if x_initialized {
+        step("scope exit; flag still set, so x is dropped");
+        unsafe { ManuallyDrop::drop(&mut x) }; // Safety: the flag says x still holds its value.
+    } else {
+        step("scope exit; flag clear, nothing to drop");
+    }
+}
+
+pub fn run() {
+    for c in [true, false] {
+        println!("c = {}:", c);
+        conditional_move(c);
+        println!("  and with the drop flag by hand:");
+        conditional_move_by_hand(c);
+    }
+    trace::take_log();
+
+    // Either way x is dropped exactly once: by f when c is true, at the end of the scope when it's false. The flag costs a byte of stack and a test at scope exit, and only for variables that are conditionally moved.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::Event::{self, NoisyDrop, Statement};
+
+    fn dropped(seq: usize) -> Event {
+        NoisyDrop { seq, text: "x".to_string() }
+    }
+
+    #[test]
+    fn moved_value_is_dropped_by_the_callee() {
+        trace::reset();
+        conditional_move(true);
+        assert_eq!(trace::take_log(), vec![Statement("let x"), dropped(1), Statement("f(x)"), Statement("scope exit")]);
+        conditional_move_by_hand(true);
+        assert_eq!(trace::take_log(), vec![
+            Statement("let x; flag set"),
+            dropped(2),
+            Statement("f(x); flag cleared by the move"),
+            Statement("scope exit; flag clear, nothing to drop"),
+        ]);
+    }
+
+    #[test]
+    fn unmoved_value_is_dropped_at_scope_exit() {
+        trace::reset();
+        conditional_move(false);
+        assert_eq!(trace::take_log(), vec![Statement("let x"), Statement("scope exit"), dropped(1)]);
+        conditional_move_by_hand(false);
+        assert_eq!(trace::take_log(), vec![
+            Statement("let x; flag set"),
+            Statement("scope exit; flag still set, so x is dropped"),
+            dropped(2),
+        ]);
+    }
+}
@@ -0,0 +1,90 @@
+// One module per section of the chapter. Each section keeps its prose as comments and exposes a
+// run() that executes the legal variants of the code it discusses. The illegal variants stay in the
+// comments so the reasoning is still there to read, each labelled "rejected by rustc:" so explain
+// doesn't print it as though it were the code that runs. Notes about a section's helper functions, rather
+// than about the chapter, are /// doc comments, which explain leaves out.
+
+pub mod control_flow;
+pub mod copy_types;
+pub mod drop_flags;
+pub mod indexed_content;
+pub mod more_moves;
+pub mod moves;
+pub mod rc_arc;
+
+pub struct Section {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub run: fn(),
+    // The module's own source, so explain can show the prose that goes with the code.
+    pub source: &'static str,
+}
+
+// In the order they appear in the chapter, so their numbers match the chapter's. Drop Flags isn't a
+// section of the chapter, so it comes after them all.
+pub const SECTIONS: &[Section] = &[
+    Section { name: "moves", title: "Moves", run: moves::run, source: include_str!("moves.rs") },
+    Section { name: "more-moves", title: "More Operations That Move", run: more_moves::run, source: include_str!("more_moves.rs") },
+    Section { name: "control-flow", title: "Moves and Control Flow", run: control_flow::run, source: include_str!("control_flow.rs") },
+    Section { name: "indexed-content", title: "Moves and Indexed Content", run: indexed_content::run, source: include_str!("indexed_content.rs") },
+    Section { name: "copy-types", title: "Copy Types: The Exception to Moves", run: copy_types::run, source: include_str!("copy_types.rs") },
+    Section { name: "rc-arc", title: "Rc and Arc: Shared Ownership", run: rc_arc::run, source: include_str!("rc_arc.rs") },
+    Section { name: "drop-flags", title: "Drop Flags", run: drop_flags::run, source: include_str!("drop_flags.rs") },
+];
+
+// Sections can be named by their short name, their number in the list, or their title in any case.
+pub fn find(name: &str) -> Option<&'static Section> {
+    if let Ok(n) = name.parse::<usize>() {
+        return n.checked_sub(1).and_then(|i| SECTIONS.get(i));
+    }
+    SECTIONS.iter().find(|section| section.name == name || section.title.eq_ignore_ascii_case(name))
+}
+
+impl Section {
+    // The comment lines of the section with the leading // stripped. Code is left out, but code that
+    // was commented out because it doesn't compile is part of the explanation, so that stays, with
+    // its label. Doc comments are about the section's code rather than the chapter, so they're left
+    // out too.
+    pub fn prose(&self) -> Vec<&'static str> {
+        let mut lines = Vec::new();
+        for line in self.source.lines() {
+            let line = line.trim_start();
+            if line.starts_with("///") {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix("//") {
+                lines.push(comment.strip_prefix(' ').unwrap_or(comment));
+            } else if line.is_empty() && lines.last().is_some_and(|last: &&str| !last.is_empty()) {
+                lines.push("");
+            }
+        }
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_follow_the_chapter() {
+        let names: Vec<Option<&str>> = ["3", "4", "6", "7", "Drop Flags"].iter().map(|n| find(n).map(|s| s.name)).collect();
+        assert_eq!(names, [Some("control-flow"), Some("indexed-content"), Some("rc-arc"), Some("drop-flags"), Some("drop-flags")]);
+    }
+
+    #[test]
+    fn prose_leaves_out_doc_comments() {
+        let section = Section { name: "x", title: "X", run: || {}, source: "// The chapter.\n/// A helper.\nfn f() {}\n// let t = s;\n" };
+        assert_eq!(section.prose(), ["The chapter.", "let t = s;"]);
+    }
+
+    #[test]
+    fn rejected_code_is_labelled() {
+        let prose = find("moves").unwrap().prose();
+        assert!(prose.contains(&"rejected by rustc: let u = s;"));
+        assert!(!prose.contains(&"let u = s;"));
+    }
+}
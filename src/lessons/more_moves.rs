@@ -0,0 +1,174 @@
+// More Operations That Move
+
+// In the examples this far, we've shown initializations, providing values for variables as they come into scope in a let statement. Assigning to a variable is slightly different, in that if you move a value into a variable that was already initialized, Rust drops the variable's prior value.
+
+use crate::composers::{ComposerRegistry, Person};
+use crate::noisy::Noisy;
+use crate::trace::{self, MoveKind, Traced};
+
+/// The strings here are Noisy, so every drop is printed and logged against the statement it happened in.
+#[allow(unused_assignments)] // "Govinda" is dropped unread, which is the point
+pub fn reassign() {
+    // For example:
+    let mut s = Noisy::new("Govinda");
+    trace::statement("let mut s = \"Govinda\"");
+    s = Noisy::new("Siddhartha"); // value "Govinda" dropped here
+    trace::statement("s = \"Siddhartha\"");
+    println!("s = {}", s);
+}
+
+/// Like reassign, but s is moved out first. At the end of the scope t is dropped first, then s, in
+/// reverse order of declaration.
+pub fn reassign_after_move() {
+    let mut s = Noisy::new("Govinda");
+    trace::statement("let mut s = \"Govinda\"");
+    let t = s;
+    trace::statement("let t = s");
+    s = Noisy::new("Siddhartha"); // nothing is dropped here
+    trace::statement("s = \"Siddhartha\"");
+    println!("s = {}, t = {}", s, t);
+}
+
+#[allow(clippy::vec_init_then_push)] // the lesson is about the moves in Vec::new() and push
+pub fn run() {
+    reassign();
+
+    // In this code, when the program assigns the string "siddhartha" to s, its prior value "Govinda" gets dropped first. But consider the following:
+    reassign_after_move();
+
+    // This time, t has taken ownership of the original string from s, so that by the time we assign to s, it is uninitialized. In this scenario, no string is dropped.
+    trace::take_log();
+
+    // Rust applies move semantics to almost any use of a value. Passing arguments to functions moves ownership to the function's parameters; returning a value from a function moves ownership to the caller. Building a tuple moves the values into the tuple, and so on.
+    // For example using a previous example:
+    let mut composers = Vec::new();
+    composers.push(Person { name: Some("Palestrina".to_string()), birth: 1525 });
+    for composer in &composers {
+        println!("{}", composer);
+    }
+    // This code shows several places at which moves occur, beyong initialization and assignment:
+    // 1. Returning values from a function
+        // The call Vec::new() constructs a new vector, and returns, not a pointer to the vector, but the vector itself. Its ownership moves from Vec::new to the variable composers. Similarly, the to_string call returns a fresh String instance.
+    // 2. Constructing new values
+        // The name field of the new Person structure is initialized with the return value of to_string. The structure takes ownership of the string.
+    // 3. Passing values to a function
+        // The entire Person structure, not just a pointer, is passed to the vector's push method, which moves it onto the end of the structure. The vector takes ownership of the Person, and thus becomes the indirect owner of the name String as well.
+    classify_moves();
+
+    // Moving values around like this may sound inefficient, but there are two things to keep in mind. First, the moves always apply to the value proper, not the heap storage they own. For vectors and strings, the value proper is a three-word header alone; the potentially large element arrays and text buffers sit where they are in the heap. Second, the Rust compiler's code generation is good at "seeing through" all these moves. In practice, the machine code often stores the value directly where it belongs.
+    // `cargo run --release -- bench` times the first point: a move costs size_of the value proper, so a 4 KiB array is slow to move and a Box of one isn't.
+    // `ownership-moves asm` checks that against the local compiler: it builds the push above in release mode and marks the instructions that put the Person into the vector.
+
+    // The same moves, at the scale of a real collection. ComposerRegistry takes its composers by value and hands them back by value.
+    registry(composers);
+}
+
+#[derive(Debug)]
+struct TracedPerson { name: Traced<String>, birth: i32 }
+
+fn classify_moves() {
+    println!("-- the moves in those two lines, by kind --");
+    traced_push();
+    trace::print_moves(&trace::take_log());
+}
+
+/// The two lines above again, with each value wrapped in a Traced and each move recorded with its kind.
+fn traced_push() {
+    let mut composers = Traced::new("Vec::new", Vec::new()).move_as(MoveKind::Return, "composers");
+    let name = Traced::new("to_string", "Palestrina".to_string()).move_as(MoveKind::Return, "temporary");
+    let name = name.move_as(MoveKind::Construction, "Person.name");
+    let person = Traced::new("temporary", TracedPerson { name, birth: 1525 });
+    let person = person.move_as(MoveKind::Argument, "composers[0]");
+    composers.get_mut().push(person);
+    let first = composers.get()[0].get();
+    println!("composers[0] is {}, born {}", first.name.get(), first.birth);
+}
+
+fn registry(composers: Vec<Person>) {
+    let mut registry = ComposerRegistry::new();
+    // extend moves the vector, and with it every Person, into the registry. composers is now uninitialized.
+    registry.extend(composers);
+    registry.insert(Person::new("Dowland".to_string(), 1563));
+    registry.insert(Person::new("Lully".to_string(), 1632));
+    registry.insert(Person::new("Monteverdi".to_string(), 1567));
+    println!("{} composers registered", registry.len());
+
+    for (century, composers) in registry.by_century() {
+        let names: Vec<&str> = composers.iter().filter_map(|c| c.name()).collect();
+        println!("  century {}: {}", century, names.join(", "));
+    }
+    if let Some(lully) = registry.get("Lully") {
+        println!("  Lully was born in {}", lully.birth);
+    }
+
+    // remove moves the Person out: the caller owns it now, and the registry no longer has it.
+    let palestrina = registry.remove("Palestrina").expect("Palestrina was registered");
+    println!("removed {}", palestrina);
+
+    // into_sorted consumes the registry, so every remaining Person moves out to the vector.
+    let everyone = registry.into_sorted();
+    let names: Vec<String> = everyone.into_iter().filter_map(|c| c.name).collect();
+    println!("oldest first: {}", names.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::Event::{self, Move, NoisyDrop, Statement};
+
+    fn dropped(seq: usize, text: &str) -> Event {
+        NoisyDrop { seq, text: text.to_string() }
+    }
+
+    #[test]
+    fn reassignment_drops_the_old_value() {
+        trace::reset();
+        reassign();
+        assert_eq!(trace::take_log(), vec![
+            Statement("let mut s = \"Govinda\""),
+            dropped(1, "Govinda"),
+            Statement("s = \"Siddhartha\""),
+            dropped(2, "Siddhartha"),
+        ]);
+    }
+
+    #[test]
+    fn reassignment_after_a_move_drops_nothing() {
+        trace::reset();
+        reassign_after_move();
+        assert_eq!(trace::take_log(), vec![
+            Statement("let mut s = \"Govinda\""),
+            Statement("let t = s"),
+            Statement("s = \"Siddhartha\""),
+            dropped(1, "Govinda"),
+            dropped(2, "Siddhartha"),
+        ]);
+    }
+
+    #[test]
+    fn the_push_moves_three_ways() {
+        trace::take_log();
+        traced_push();
+        let log = trace::take_log();
+        let kinds: Vec<(MoveKind, Vec<(&str, &str)>)> = trace::moves_by_kind(&log).into_iter()
+            .map(|(kind, moves)| {
+                let moves = moves.into_iter().filter_map(|event| match event {
+                    Move { from, to, .. } => Some((from.as_str(), to.as_str())),
+                    _ => None,
+                });
+                (kind, moves.collect())
+            })
+            .collect();
+        assert_eq!(kinds, [
+            (MoveKind::Return, vec![("Vec::new", "composers"), ("to_string", "temporary")]),
+            (MoveKind::Construction, vec![("temporary", "Person.name")]),
+            (MoveKind::Argument, vec![("temporary", "composers[0]")]),
+        ]);
+        // A return value has no variable to leave uninitialized.
+        for (kind, moves) in trace::moves_by_kind(&log) {
+            for event in moves {
+                assert_eq!(event.to_string().contains("is now uninitialized"), kind != MoveKind::Return, "{}", event);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// Moves
+
+// In Rust, for most types, operations like assigning a value to a variable, passing it to a function, or returning it from a function don't copy the value, they move it. The source relinquishes ownership of the value to the destination, and becomes uninitialized. The destination now controls the value's lifetime. Rust programs build up and tear down complex structures one value at a time, one move at a time.
+
+// Consider the following Python code:
+// s = ['udon', 'ramen', 'soba']
+// t = s
+// u = s
+// Each Python object carries a reference count, tracking the number of values that are currently referring to it. To start, since only s is pointing to the list, the list's reference count is 1. Since the list is the only object pointing to the strings, each of their reference counts is also 1. So what happens with t and u? Python implements assignment simply by making the destination point to the same object as the source, and incrementing the object's reference count.
+// So this means that when it's just s, there is 1 reference count, with t and u, the reference count is now 3. So if we want to free a value, we must keep in mind how many references there are and where they are.
+
+// For C++ instead of reference counts, the language makes a copy of each list to t and u. So instead of 3 variables referencing a list with 3 values as in Python, C++ will have 3 lists, with 9 total values, tripling the memory usage. This is a large issue to contend with in C++.
+// See page 132 and 133 for diagrams to illustrate this. run() prints the same object graphs from the Python and C++ models.
+
+use crate::counting;
+use crate::cpp;
+use crate::diagram;
+use crate::layout;
+use crate::python;
+use crate::trace::{self, Traced};
+
+// In a sense, C++ and Python have chosen opposite trade-offs. Python makes assignment cheap, at the expense of requiring reference counting (and in the general case, garbage collection). C++ keeps the ownership of all the memory clear, at the expense of making assignment carry out a deep copy of the object. Deep copies can be bad as they can be expensive and there are more practical alternatives.
+
+pub fn run() {
+    // First, the Python script above, run through a model of Python's heap so we can watch the counts:
+    python_model();
+    // And the C++ version, through a model of std::vector<std::string> copies:
+    cpp_model();
+
+    // So how does the above work in Rust?
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    println!("{}", diagram::vec_of_strings("s owns the vector, which owns the strings", "s", &s));
+    let t = s;
+    println!("{}", diagram::vec_of_strings("after let t = s;, t owns the same blocks and s is uninitialized", "t", &t));
+    // rejected by rustc: let u = s;
+    println!("t = {:?}", t);
+
+    // Like C and C++ Rust puts plain string literals like "udon" in read-only memory. So to make the comparison equal we call to_string here to get heap-allocated String values.
+    // Like the C++, Rust will allocate s a stack frame which is allocated to the heap which contains the three strings. See page 135 for diagram, or the two diagrams run() prints above: only the header on the stack changes hands.
+    // Recall that in Rust, assignments of most types move the value from the source to the destination, leaving the source uninitialized. So looking at t, it takes on the vector's three header fields from s, t now owns the vector. The vector's elements stayed just where they were, and nothing happened to the strings either. Every value still has a single owner, although one has changed hands. No changes to reference counts and the compiler considers s uninitialized.
+    // When we get to u, it would assign the uninitialized value s to u. Rust prohibits using uninitialized values, so the compiler rejects the code with a "ownership_double_move" error. That is why let u = s; is commented out above. Today's rustc reports it as error[E0382]: use of moved value: `s`, pointing at t = s as "value moved here" (see tests/compile-fail/double_move.rs).
+
+    // So like Python Rust's assignment is cheap, but it also is like C++ where ownership is always clear. A win win. The price of this however is that we must explicitly ask for copies when we want them. To do so, me must call the vector's clone method which perform a deep copy of the vector and its elements:
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    let t = s.clone();
+    let u = s.clone();
+    println!("s = {:?}, t = {:?}, u = {:?}", s, t, u);
+    // We can also re-create Python's behaviour by using Rust's reference-counted pointer types which will be discussed shortly.
+
+    replay();
+    inspect_layout();
+    count_allocations();
+}
+
+const PYTHON_SCRIPT: &str = "s = ['udon', 'ramen', 'soba']
+t = s
+u = s";
+
+fn python_model() {
+    println!("-- the Python version --");
+    let mut interpreter = python::Interpreter::new();
+    interpreter.run_script(PYTHON_SCRIPT).expect("the chapter's Python script runs");
+    println!("one list with a count of 3, each string with a count of 1\n");
+}
+
+/// Runs the chapter's C++ on the model, then again with the small string optimization: real
+/// implementations keep short strings inside the header, which changes the count but not the tripling.
+fn cpp_model() {
+    println!("-- the C++ version --");
+    let mut machine = cpp::Machine::new();
+    machine.construct("s", &["udon", "ramen", "soba"]);
+    println!("std::vector<std::string> s = {{\"udon\", \"ramen\", \"soba\"}};\n{}", machine);
+    let one_list = machine.stats();
+    machine.copy("t", "s");
+    println!("std::vector<std::string> t = s;\n{}", machine);
+    machine.copy("u", "s");
+    println!("std::vector<std::string> u = s;\n{}", machine);
+
+    let lists = ["s", "t", "u"].iter().filter_map(|name| machine.variable(name)).count();
+    let values: usize = ["s", "t", "u"].iter().filter_map(|name| machine.variable(name)).map(|v| v.elements.len()).sum();
+    let total = machine.stats();
+    println!("{} lists, {} values, {}x the allocations and {}x the bytes of s alone",
+             lists, values, total.allocations / one_list.allocations, total.bytes_allocated / one_list.bytes_allocated);
+
+    let mut machine = cpp::Machine::with_small_string_optimization();
+    machine.construct("s", &["udon", "ramen", "soba"]);
+    machine.copy("t", "s");
+    machine.copy("u", "s");
+    println!("with the small string optimization:\n{}", machine);
+}
+
+/// The same two examples again, with every value wrapped in a Traced so the moves and clones show up
+/// in a timeline rather than having to be taken on faith. move_to takes s by value, so let u = s;
+/// after it is still rejected.
+fn replay() {
+    println!("-- replaying with tracing --");
+    {
+        let s = Traced::new("s", noodles());
+        let t = s.move_to("t");
+        println!("t owns the same elements s built: {:?}", t.get());
+    }
+    {
+        let s = Traced::new("s", noodles());
+        let t = s.clone_to("t");
+        let u = s.clone_to("u");
+        println!("s, t and u own vectors #{}, #{} and #{}", s.id(), t.id(), u.id());
+    }
+    trace::print_timeline(&trace::take_log());
+}
+
+fn noodles() -> Vec<Traced<String>> {
+    vec![
+        Traced::new("element 0", "udon".to_string()),
+        Traced::new("element 1", "ramen".to_string()),
+        Traced::new("element 2", "soba".to_string()),
+    ]
+}
+
+/// The header words and heap addresses for s before and after let t = s;, and for a clone. The move
+/// copies only the header: the element buffer and the text buffers stay at the same addresses. The
+/// clone gets a fresh buffer and fresh text for every string.
+fn inspect_layout() {
+    println!("-- memory layout --");
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    let before = layout::inspect(&s);
+    println!("s before the move:\n{}", before);
+
+    let t = s;
+    let after = layout::inspect(&t);
+    println!("t after let t = s;:\n{}", after);
+    assert_eq!(before.buffer, after.buffer);
+    for (b, a) in before.strings.iter().zip(&after.strings) {
+        assert_eq!(b.buffer, a.buffer);
+    }
+    println!("same element buffer, same text buffers: only the header moved\n");
+
+    let u = t.clone();
+    let cloned = layout::inspect(&u);
+    println!("u = t.clone():\n{}", cloned);
+    assert_ne!(after.buffer, cloned.buffer);
+    for (a, c) in after.strings.iter().zip(&cloned.strings) {
+        assert_ne!(a.buffer, c.buffer);
+    }
+    println!("new element buffer and new text buffers: the clone is a deep copy");
+}
+
+/// With the counting allocator installed, the heap itself can confirm that a move allocates nothing
+/// and a clone allocates a new buffer plus one block per string.
+fn count_allocations() {
+    println!("-- allocations --");
+    if !counting::enabled() {
+        println!("build with --features count-allocations to count them");
+        return;
+    }
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    let (t, moved) = counting::measure(|| s);
+    println!("let t = s;         {}", moved);
+    let (u, cloned) = counting::measure(|| t.clone());
+    println!("let u = t.clone(); {}", cloned);
+    assert_eq!(moved.allocations, 0);
+    assert_eq!(cloned.allocations, 4);
+    drop(u);
+}
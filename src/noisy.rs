@@ -0,0 +1,39 @@
+// A string that announces its own drop. Each drop takes the next number from the trace log's counter
+// and goes into that log, alongside the markers a lesson puts down after each statement with
+// trace::statement, so the log shows which statement each value died in.
+
+use std::fmt;
+use std::mem;
+
+use crate::trace::{self, Event};
+
+pub struct Noisy {
+    text: String,
+}
+
+impl Noisy {
+    pub fn new(text: &str) -> Noisy {
+        Noisy { text: text.to_string() }
+    }
+}
+
+// A clone says so, so a log can tell it from the original when both are dropped.
+impl Clone for Noisy {
+    fn clone(&self) -> Noisy {
+        Noisy::new(&format!("clone of {}", self.text))
+    }
+}
+
+impl fmt::Display for Noisy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        let seq = trace::next_id();
+        println!("    drop #{}: {:?}", seq, self.text);
+        trace::record(Event::NoisyDrop { seq, text: mem::take(&mut self.text) });
+    }
+}
@@ -0,0 +1,329 @@
+// A toy model of Python's object heap, just enough to run the chapter's
+//     s = ['udon', 'ramen', 'soba']
+//     t = s
+//     u = s
+// and watch the reference counts. Every object carries a count of the references to it; binding a
+// name to an object increments it, rebinding or del-ing the name decrements it, and an object whose
+// count reaches zero is freed, releasing its references to anything it held.
+
+use std::fmt;
+
+pub type ObjId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    List(Vec<ObjId>),
+}
+
+#[derive(Debug)]
+pub struct PyObject {
+    pub refcount: usize,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Str(String),
+    List(Vec<Expr>),
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Stmt {
+    Assign(String, Expr),
+    Del(String),
+}
+
+// Sizes as in CPython 3 on a 64-bit target. A str is one allocation with its text inline; a list is
+// the list object plus a separately allocated array of item pointers.
+pub const STR_HEADER: usize = 49;
+pub const LIST_OBJECT: usize = 56;
+pub const POINTER: usize = 8;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub allocations: usize,
+    pub bytes_allocated: usize,
+    // Increments and decrements after an object's creation.
+    pub refcount_updates: usize,
+}
+
+#[derive(Default)]
+pub struct Interpreter {
+    // Indexed by ObjId. Freed objects leave a None behind so ids are never reused.
+    heap: Vec<Option<PyObject>>,
+    // In the order the names were first bound, which is the order people expect to see them in.
+    names: Vec<(String, ObjId)>,
+    stats: Stats,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter::default()
+    }
+
+    // Runs each line of the script, printing it followed by the state of the heap.
+    pub fn run_script(&mut self, script: &str) -> Result<(), String> {
+        for (number, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            self.exec(line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            println!(">>> {}", line);
+            print!("{}", self);
+        }
+        Ok(())
+    }
+
+    pub fn exec(&mut self, line: &str) -> Result<(), String> {
+        match parse_stmt(line)? {
+            Stmt::Assign(name, expr) => {
+                // Python evaluates the right-hand side before touching the old binding, so t = t works.
+                let id = self.eval(&expr)?;
+                let old = match self.names.iter_mut().find(|(n, _)| *n == name) {
+                    Some(binding) => Some(std::mem::replace(&mut binding.1, id)),
+                    None => {
+                        self.names.push((name, id));
+                        None
+                    }
+                };
+                if let Some(old) = old {
+                    self.decref(old);
+                }
+            }
+            Stmt::Del(name) => {
+                let index = self.names.iter().position(|(n, _)| *n == name)
+                    .ok_or_else(|| format!("NameError: name '{}' is not defined", name))?;
+                let (_, id) = self.names.remove(index);
+                self.decref(id);
+            }
+        }
+        Ok(())
+    }
+
+    // Evaluates an expression to an object, counting the new reference the caller is about to hold.
+    fn eval(&mut self, expr: &Expr) -> Result<ObjId, String> {
+        match expr {
+            Expr::Str(s) => Ok(self.alloc(Value::Str(s.clone()))),
+            Expr::List(items) => {
+                let mut ids = Vec::new();
+                for item in items {
+                    ids.push(self.eval(item)?);
+                }
+                Ok(self.alloc(Value::List(ids)))
+            }
+            Expr::Name(name) => {
+                let id = self.lookup(name)
+                    .ok_or_else(|| format!("NameError: name '{}' is not defined", name))?;
+                self.incref(id);
+                Ok(id)
+            }
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // How many names refer to the object directly.
+    pub fn bindings(&self, id: ObjId) -> usize {
+        self.names.iter().filter(|&&(_, n)| n == id).count()
+    }
+
+    fn alloc(&mut self, value: Value) -> ObjId {
+        let (allocations, bytes) = match &value {
+            Value::Str(s) => (1, STR_HEADER + s.len()),
+            Value::List(items) if items.is_empty() => (1, LIST_OBJECT),
+            Value::List(items) => (2, LIST_OBJECT + POINTER * items.len()),
+        };
+        self.stats.allocations += allocations;
+        self.stats.bytes_allocated += bytes;
+        self.heap.push(Some(PyObject { refcount: 1, value }));
+        self.heap.len() - 1
+    }
+
+    fn incref(&mut self, id: ObjId) {
+        self.stats.refcount_updates += 1;
+        self.object_mut(id).refcount += 1;
+    }
+
+    fn decref(&mut self, id: ObjId) {
+        self.stats.refcount_updates += 1;
+        let object = self.object_mut(id);
+        object.refcount -= 1;
+        if object.refcount == 0 {
+            if let Some(PyObject { value: Value::List(items), .. }) = self.heap[id].take() {
+                for item in items {
+                    self.decref(item);
+                }
+            }
+        }
+    }
+
+    fn object_mut(&mut self, id: ObjId) -> &mut PyObject {
+        self.heap[id].as_mut().expect("reference to a freed object")
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<ObjId> {
+        self.names.iter().find(|(n, _)| n == name).map(|&(_, id)| id)
+    }
+
+    pub fn object(&self, id: ObjId) -> Option<&PyObject> {
+        self.heap.get(id).and_then(|object| object.as_ref())
+    }
+
+    pub fn live_objects(&self) -> impl Iterator<Item = (ObjId, &PyObject)> {
+        self.heap.iter().enumerate().filter_map(|(id, object)| object.as_ref().map(|object| (id, object)))
+    }
+
+    fn repr(&self, id: ObjId) -> String {
+        match self.object(id).map(|object| &object.value) {
+            Some(Value::Str(s)) => format!("'{}'", s),
+            Some(Value::List(items)) => {
+                let items: Vec<String> = items.iter().map(|&item| self.repr(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            None => "<freed>".to_string(),
+        }
+    }
+}
+
+// One line per live object: its id, count, and which names (if any) refer to it directly.
+impl fmt::Display for Interpreter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (id, object) in self.live_objects() {
+            let names: Vec<&str> = self.names.iter().filter(|&&(_, n)| n == id).map(|(name, _)| name.as_str()).collect();
+            write!(f, "    #{} refcount {} {}", id, object.refcount, self.repr(id))?;
+            if !names.is_empty() {
+                write!(f, " <- {}", names.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_stmt(line: &str) -> Result<Stmt, String> {
+    if let Some(name) = line.strip_prefix("del ") {
+        return Ok(Stmt::Del(parse_name(name.trim())?));
+    }
+    let (name, expr) = line.split_once('=').ok_or_else(|| format!("SyntaxError: {}", line))?;
+    let mut rest = expr.trim();
+    let expr = parse_expr(&mut rest)?;
+    if !rest.is_empty() {
+        return Err(format!("SyntaxError: unexpected {:?}", rest));
+    }
+    Ok(Stmt::Assign(parse_name(name.trim())?, expr))
+}
+
+fn parse_name(name: &str) -> Result<String, String> {
+    let valid = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if valid {
+        Ok(name.to_string())
+    } else {
+        Err(format!("SyntaxError: invalid name {:?}", name))
+    }
+}
+
+// Parses one expression off the front of *input, leaving whatever follows it.
+fn parse_expr(input: &mut &str) -> Result<Expr, String> {
+    *input = input.trim_start();
+    if let Some(rest) = input.strip_prefix('[') {
+        *input = rest.trim_start();
+        let mut items = Vec::new();
+        if let Some(rest) = input.strip_prefix(']') {
+            *input = rest.trim_start();
+            return Ok(Expr::List(items));
+        }
+        loop {
+            items.push(parse_expr(input)?);
+            *input = input.trim_start();
+            if let Some(rest) = input.strip_prefix(',') {
+                *input = rest;
+            } else if let Some(rest) = input.strip_prefix(']') {
+                *input = rest.trim_start();
+                return Ok(Expr::List(items));
+            } else {
+                return Err(format!("SyntaxError: expected ',' or ']' at {:?}", input));
+            }
+        }
+    }
+    if let Some(quote) = input.chars().next().filter(|&c| c == '\'' || c == '"') {
+        let body = &input[1..];
+        let end = body.find(quote).ok_or("SyntaxError: unterminated string")?;
+        let s = body[..end].to_string();
+        *input = body[end + 1..].trim_start();
+        return Ok(Expr::Str(s));
+    }
+    let end = input.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.len());
+    let name = parse_name(&input[..end])?;
+    *input = input[end..].trim_start();
+    Ok(Expr::Name(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(lines: &[&str]) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        for line in lines {
+            interpreter.exec(line).unwrap();
+        }
+        interpreter
+    }
+
+    // Each live object as its repr and count.
+    fn live(interpreter: &Interpreter) -> Vec<(String, usize)> {
+        interpreter.live_objects().map(|(id, object)| (interpreter.repr(id), object.refcount)).collect()
+    }
+
+    #[test]
+    fn the_chapter_script_shares_one_list() {
+        let interpreter = run(&["s = ['udon', 'ramen', 'soba']", "t = s", "u = s"]);
+        let list = interpreter.lookup("s").unwrap();
+        assert_eq!(interpreter.lookup("u"), Some(list));
+        assert_eq!(interpreter.object(list).unwrap().refcount, 3);
+        assert_eq!(interpreter.bindings(list), 3);
+        assert_eq!(interpreter.stats().allocations, 5);
+    }
+
+    #[test]
+    fn rebinding_drops_a_reference_to_the_old_object() {
+        let mut interpreter = run(&["s = ['udon', 'ramen']", "t = s", "s = 'soba'"]);
+        assert_eq!(live(&interpreter), [
+            ("'udon'".to_string(), 1), ("'ramen'".to_string(), 1), ("['udon', 'ramen']".to_string(), 1), ("'soba'".to_string(), 1),
+        ]);
+        // The right-hand side is evaluated first, so rebinding a name to itself keeps the object alive.
+        interpreter.exec("t = t").unwrap();
+        assert_eq!(interpreter.object(interpreter.lookup("t").unwrap()).unwrap().refcount, 1);
+    }
+
+    #[test]
+    fn del_unbinds_the_name() {
+        let mut interpreter = run(&["s = ['udon']", "t = s", "del s"]);
+        assert_eq!(interpreter.lookup("s"), None);
+        assert_eq!(live(&interpreter), [("'udon'".to_string(), 1), ("['udon']".to_string(), 1)]);
+        assert_eq!(interpreter.exec("del s"), Err("NameError: name 's' is not defined".to_string()));
+        assert_eq!(interpreter.exec("u = s"), Err("NameError: name 's' is not defined".to_string()));
+    }
+
+    #[test]
+    fn a_list_freed_at_zero_releases_its_strings() {
+        let mut interpreter = run(&["s = ['udon', 'ramen', 'soba']", "t = s", "del s"]);
+        interpreter.exec("t = 'shirataki'").unwrap();
+        assert_eq!(live(&interpreter), [("'shirataki'".to_string(), 1)]);
+        for id in 0..4 {
+            assert!(interpreter.object(id).is_none());
+        }
+
+        // A string in two lists, and bound to a name, goes only when the last of them does.
+        let mut interpreter = run(&["s = 'udon'", "l = [s, s]", "m = [s]", "del s", "del l"]);
+        assert_eq!(live(&interpreter), [("'udon'".to_string(), 1), ("['udon']".to_string(), 1)]);
+        interpreter.exec("del m").unwrap();
+        assert_eq!(live(&interpreter), []);
+    }
+}
@@ -0,0 +1,195 @@
+// The cost of s = ['udon', 'ramen', 'soba']; t = s; u = s under each strategy the chapter discusses,
+// side by side. The Python and C++ columns come from running the script through the models in
+// python.rs and cpp.rs; the Rust columns measure the real values, reading their heap usage off
+// capacity() and their owners off Rc::strong_count.
+
+use std::mem;
+use std::rc::Rc;
+
+use crate::cpp;
+use crate::python;
+
+pub struct Column {
+    pub strategy: &'static str,
+    pub allocations: usize,
+    pub bytes: usize,
+    pub refcount_updates: usize,
+    // How many copies of the list exist, and how many variables own the original one.
+    pub lists: usize,
+    pub owners: usize,
+}
+
+// The heap blocks behind a Vec<String>: its element buffer and each string's text, if allocated.
+#[allow(clippy::ptr_arg)]
+fn vec_heap(v: &Vec<String>) -> (usize, usize) {
+    let blocks = std::iter::once(v.capacity() * mem::size_of::<String>())
+        .chain(v.iter().map(|s| s.capacity()))
+        .filter(|&bytes| bytes > 0);
+    blocks.fold((0, 0), |(allocations, total), bytes| (allocations + 1, total + bytes))
+}
+
+// How many distinct element buffers the vectors have between them, and how many of them still use
+// the buffer at original. A move leaves the buffer where it was, and a clone gets one of its own.
+fn buffers(original: *const String, vectors: &[&[String]]) -> (usize, usize) {
+    let mut distinct: Vec<*const String> = vectors.iter().map(|v| v.as_ptr()).collect();
+    distinct.sort();
+    distinct.dedup();
+    (distinct.len(), vectors.iter().filter(|v| v.as_ptr() == original).count())
+}
+
+fn noodles() -> Vec<String> {
+    vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()]
+}
+
+fn python_column() -> Column {
+    let mut interpreter = python::Interpreter::new();
+    for line in &["s = ['udon', 'ramen', 'soba']", "t = s", "u = s"] {
+        interpreter.exec(line).expect("the chapter's Python script runs");
+    }
+    let s = interpreter.lookup("s").expect("s is bound");
+    let stats = interpreter.stats();
+    let lists = interpreter.live_objects()
+        .filter(|(_, object)| matches!(object.value, python::Value::List(_)))
+        .count();
+    Column {
+        strategy: "Python t = s",
+        allocations: stats.allocations,
+        bytes: stats.bytes_allocated,
+        refcount_updates: stats.refcount_updates,
+        lists,
+        owners: interpreter.bindings(s),
+    }
+}
+
+fn cpp_column() -> Column {
+    let mut machine = cpp::Machine::new();
+    machine.construct("s", &["udon", "ramen", "soba"]);
+    machine.copy("t", "s");
+    machine.copy("u", "s");
+    let stats = machine.stats();
+    let vectors: Vec<&cpp::CppVector> = ["s", "t", "u"].iter().filter_map(|name| machine.variable(name)).collect();
+    let mut distinct: Vec<cpp::BlockId> = vectors.iter().map(|v| v.buffer).collect();
+    distinct.sort();
+    distinct.dedup();
+    let original = vectors[0].buffer;
+    Column {
+        strategy: "C++ t = s",
+        allocations: stats.allocations,
+        bytes: stats.bytes_allocated,
+        // The model has no reference counts to update.
+        refcount_updates: 0,
+        lists: distinct.len(),
+        owners: vectors.iter().filter(|v| v.buffer == original).count(),
+    }
+}
+
+fn rust_move_column() -> Column {
+    let s = noodles();
+    let original = s.as_ptr();
+    let t = s;
+    // let u = s; doesn't compile, and s can't be named after the move, so t is the only owner left to
+    // count.
+    let (lists, owners) = buffers(original, &[&t]);
+    let (allocations, bytes) = vec_heap(&t);
+    Column { strategy: "Rust let t = s", allocations, bytes, refcount_updates: 0, lists, owners }
+}
+
+fn rust_clone_column() -> Column {
+    let s = noodles();
+    let t = s.clone();
+    let u = s.clone();
+    let (allocations, bytes) = [&s, &t, &u].iter()
+        .map(|v| vec_heap(v))
+        .fold((0, 0), |(a, b), (allocations, bytes)| (a + allocations, b + bytes));
+    let (lists, owners) = buffers(s.as_ptr(), &[&s, &t, &u]);
+    Column { strategy: "Rust s.clone()", allocations, bytes, refcount_updates: 0, lists, owners }
+}
+
+fn rust_rc_column() -> Column {
+    let s: Rc<Vec<String>> = Rc::new(noodles());
+    let t = s.clone();
+    let u = s.clone();
+    // The Rc's own block holds the strong and weak counts followed by the Vec header.
+    let rc_block = 2 * mem::size_of::<usize>() + mem::size_of::<Vec<String>>();
+    let (allocations, bytes) = vec_heap(&u);
+    let owners = Rc::strong_count(&t);
+    let (lists, _) = buffers(s.as_ptr(), &[&s, &t, &u]);
+    Column {
+        strategy: "Rust Rc clone",
+        allocations: allocations + 1,
+        bytes: bytes + rc_block,
+        refcount_updates: owners - 1,
+        lists,
+        owners,
+    }
+}
+
+pub fn columns() -> Vec<Column> {
+    vec![python_column(), cpp_column(), rust_move_column(), rust_clone_column(), rust_rc_column()]
+}
+
+type Row = (&'static str, fn(&Column) -> usize);
+
+pub fn print(columns: &[Column]) {
+    let rows: [Row; 5] = [
+        ("heap allocations", |c| c.allocations),
+        ("bytes allocated", |c| c.bytes),
+        ("refcount updates", |c| c.refcount_updates),
+        ("lists in memory", |c| c.lists),
+        ("owners of s's list", |c| c.owners),
+    ];
+
+    print!("{:<20}", "");
+    for column in columns {
+        print!("{:>16}", column.strategy);
+    }
+    println!();
+    for (label, value) in rows.iter() {
+        print!("{:<20}", label);
+        for column in columns {
+            print!("{:>16}", value(column));
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // allocations, refcount updates, lists and owners; bytes depend on the allocator's capacities.
+    fn counts(column: &Column) -> (usize, usize, usize, usize) {
+        (column.allocations, column.refcount_updates, column.lists, column.owners)
+    }
+
+    #[test]
+    fn python_shares_one_list_between_three_names() {
+        assert_eq!(counts(&python_column()), (5, 2, 1, 3));
+    }
+
+    #[test]
+    fn cpp_copies_everything_three_times() {
+        let column = cpp_column();
+        assert_eq!(counts(&column), (12, 0, 3, 1));
+        assert_eq!(column.bytes % 3, 0);
+    }
+
+    #[test]
+    fn a_move_leaves_one_owner_and_touches_no_counts() {
+        assert_eq!(counts(&rust_move_column()), (4, 0, 1, 1));
+    }
+
+    #[test]
+    fn clones_are_three_lists_with_one_owner_each() {
+        let column = rust_clone_column();
+        assert_eq!(counts(&column), (12, 0, 3, 1));
+        assert_eq!(column.bytes, 3 * rust_move_column().bytes);
+    }
+
+    #[test]
+    fn rc_adds_one_block_and_two_refcount_updates() {
+        let column = rust_rc_column();
+        assert_eq!(counts(&column), (5, 2, 1, 3));
+        assert!(column.bytes > rust_move_column().bytes);
+    }
+}
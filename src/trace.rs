@@ -0,0 +1,214 @@
+// Ownership tracing. Wrapping a value in Traced records when it is constructed, when it is handed
+// from one named owner to another, when it is cloned and when it is dropped. The events go into a
+// per-thread log which a lesson can take and print as a timeline.
+//
+// Every move is recorded with its kind and the line of the lesson that made it, so a log can also be
+// printed as a list of moves grouped by kind.
+//
+// The log is shared with Noisy, whose drops go into it too, along with the statement markers a lesson
+// puts down to show which statement each drop happened in.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::mem;
+use std::panic::Location;
+
+thread_local! {
+    static LOG: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<usize> = const { Cell::new(1) };
+}
+
+// The places a move can happen, in the order More Operations That Move lists them, with plain
+// initialization and assignment last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    Return,
+    Construction,
+    Argument,
+    Assignment,
+}
+
+impl MoveKind {
+    pub const ALL: [MoveKind; 4] = [MoveKind::Return, MoveKind::Construction, MoveKind::Argument, MoveKind::Assignment];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            MoveKind::Return => "Returning values from a function",
+            MoveKind::Construction => "Constructing new values",
+            MoveKind::Argument => "Passing values to a function",
+            MoveKind::Assignment => "Initialization and assignment",
+        }
+    }
+}
+
+impl fmt::Display for MoveKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            MoveKind::Return => "return",
+            MoveKind::Construction => "construction",
+            MoveKind::Argument => "argument",
+            MoveKind::Assignment => "assignment",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Construct { id: usize, owner: String, value: String },
+    // Only the value proper moves, so bytes is the size of the header, never the heap it owns.
+    Move { id: usize, from: String, to: String, bytes: usize, kind: MoveKind, at: &'static Location<'static> },
+    Clone { from: usize, to: usize, value: String },
+    Drop { id: usize, owner: String, value: String },
+    // The statement that has just finished running.
+    Statement(&'static str),
+    // A Noisy being dropped. seq comes from the same counter as the traced ids.
+    NoisyDrop { seq: usize, text: String },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Construct { id, owner, value } => write!(f, "construct #{} {} owned by {}", id, value, owner),
+            Event::Move { id, from, to, bytes, kind, at } => {
+                write!(f, "move      #{} {} -> {} by {} at {}:{} ({} bytes copied", id, from, to, kind, at.file(), at.line(), bytes)?;
+                // A return value is a temporary, so there is no variable left behind to be uninitialized.
+                match kind {
+                    MoveKind::Return => write!(f, ")"),
+                    _ => write!(f, ", {} is now uninitialized)", from),
+                }
+            }
+            Event::Clone { from, to, value } => write!(f, "clone     #{} -> #{} {}", from, to, value),
+            Event::Drop { id, owner, value } => write!(f, "drop      #{} {} owned by {}", id, value, owner),
+            Event::Statement(text) => write!(f, "after     {}", text),
+            Event::NoisyDrop { seq, text } => write!(f, "drop      #{} {:?}", seq, text),
+        }
+    }
+}
+
+pub fn record(event: Event) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+pub fn next_id() -> usize {
+    NEXT_ID.with(|next| {
+        let id = next.get();
+        next.set(id + 1);
+        id
+    })
+}
+
+// Marks the end of a statement in the log.
+pub fn statement(text: &'static str) {
+    record(Event::Statement(text));
+}
+
+// Removes and returns everything recorded on this thread so far.
+pub fn take_log() -> Vec<Event> {
+    LOG.with(|log| mem::take(&mut *log.borrow_mut()))
+}
+
+// Clears the log and starts the ids again from 1.
+#[cfg(test)]
+pub fn reset() {
+    take_log();
+    NEXT_ID.with(|next| next.set(1));
+}
+
+pub fn print_timeline(events: &[Event]) {
+    for (step, event) in events.iter().enumerate() {
+        println!("{:>3}. {}", step + 1, event);
+    }
+}
+
+// The moves in events, grouped by kind in the order of MoveKind::ALL. Kinds with no moves are left out.
+pub fn moves_by_kind(events: &[Event]) -> Vec<(MoveKind, Vec<&Event>)> {
+    MoveKind::ALL.iter()
+        .map(|&kind| (kind, events.iter().filter(|event| matches!(event, Event::Move { kind: k, .. } if *k == kind)).collect()))
+        .filter(|(_, moves): &(MoveKind, Vec<&Event>)| !moves.is_empty())
+        .collect()
+}
+
+// The moves in events, numbered by kind.
+pub fn print_moves(events: &[Event]) {
+    for (number, (kind, moves)) in moves_by_kind(events).into_iter().enumerate() {
+        println!("{}. {}", number + 1, kind.description());
+        for event in moves {
+            if let Event::Move { id, from, to, at, .. } = event {
+                println!("     #{} {} -> {} ({}:{})", id, from, to, at.file(), at.line());
+            }
+        }
+    }
+}
+
+pub struct Traced<T: fmt::Debug> {
+    id: usize,
+    owner: String,
+    value: T,
+}
+
+impl<T: fmt::Debug> Traced<T> {
+    pub fn new(owner: &str, value: T) -> Traced<T> {
+        let id = next_id();
+        record(Event::Construct { id, owner: owner.to_string(), value: format!("{:?}", value) });
+        Traced { id, owner: owner.to_string(), value }
+    }
+
+    // Hands the value to a new owner. Taking self by value means the compiler treats the old binding
+    // as uninitialized afterwards, exactly as it would for a plain let t = s;
+    #[track_caller]
+    pub fn move_to(self, owner: &str) -> Traced<T> {
+        self.move_as(MoveKind::Assignment, owner)
+    }
+
+    // The same, for a move of the given kind. The move is recorded against the caller's line.
+    #[track_caller]
+    pub fn move_as(mut self, kind: MoveKind, owner: &str) -> Traced<T> {
+        let at = Location::caller();
+        record(Event::Move { id: self.id, from: self.owner.clone(), to: owner.to_string(), bytes: mem::size_of::<T>(), kind, at });
+        self.owner = owner.to_string();
+        self
+    }
+
+    pub fn clone_to(&self, owner: &str) -> Traced<T>
+        where T: Clone
+    {
+        let mut copy = self.clone();
+        copy.owner = owner.to_string();
+        copy
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// Cloning a Traced clones the value inside it, so a Traced<Vec<Traced<String>>> records a clone for
+// the vector and another for every element.
+impl<T: fmt::Debug + Clone> Clone for Traced<T> {
+    fn clone(&self) -> Traced<T> {
+        let value = self.value.clone();
+        let id = next_id();
+        record(Event::Clone { from: self.id, to: id, value: format!("{:?}", value) });
+        Traced { id, owner: format!("clone of #{}", self.id), value }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Traced<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {:?}", self.id, self.value)
+    }
+}
+
+impl<T: fmt::Debug> Drop for Traced<T> {
+    fn drop(&mut self) {
+        record(Event::Drop { id: self.id, owner: self.owner.clone(), value: format!("{:?}", self.value) });
+    }
+}
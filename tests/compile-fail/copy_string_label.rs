@@ -0,0 +1,7 @@
+// Copy Types: deriving Copy for a struct with a String field.
+#![allow(unused)]
+
+#[derive(Copy, Clone)]
+struct StringLabel { name: String }
+
+fn main() {}
@@ -0,0 +1,8 @@
+// Moves: assigning s to u after it has already moved to t.
+#![allow(unused)]
+
+fn main() {
+    let s = vec!["udon".to_string(), "ramen".to_string(), "soba".to_string()];
+    let t = s;
+    let u = s;
+}
@@ -0,0 +1,11 @@
+// Moves and Indexed Content: taking the name out of composers[0] without Option::take.
+#![allow(unused)]
+
+struct Person { name: Option<String>, birth: i32 }
+
+fn main() {
+    let mut composers = Vec::new();
+    composers.push(Person { name: Some("Palestrina".to_string()), birth: 1525 });
+
+    let first_name = composers[0].name;
+}
@@ -0,0 +1,13 @@
+// Moves and Indexed Content: pulling elements out of a vector by index.
+#![allow(unused)]
+
+fn main() {
+    // Build a vector of the strings "101", "102", ... "105"
+    let mut v = Vec::new();
+    for i in 101 .. 106 {
+        v.push(i.to_string());
+    }
+
+    // Pull out random elements from the vector.
+    let third = v[2];
+}
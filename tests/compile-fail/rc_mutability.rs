@@ -0,0 +1,9 @@
+// Rc and Arc: appending to the String behind an Rc.
+#![allow(unused)]
+
+use std::rc::Rc;
+
+fn main() {
+    let s: Rc<String> = Rc::new("shirataki".to_string());
+    s.push_str("noodles");
+}
@@ -0,0 +1,12 @@
+// Copy Types: a non-Copy Label is moved into print and then used again.
+#![allow(unused)]
+
+struct Label { number: u32 }
+
+fn print(l: Label) { println!("STAMP: {}", l.number);}
+
+fn main() {
+    let l = Label { number: 3 };
+    print(l);
+    println!("My label number is: {}", l.number);
+}
@@ -0,0 +1,72 @@
+// The chapter describes several programs that Rust refuses to compile. Each one lives under
+// tests/compile-fail/ and is handed to the local rustc here, so if a toolchain upgrade changes the
+// error code or wording, these tests fail and the notes in src/lessons/ get updated to match.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// Compiles tests/compile-fail/<name>.rs and returns rustc's diagnostics. Panics if it compiled.
+fn compile_fail(name: &str) -> String {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/compile-fail").join(format!("{}.rs", name));
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let output = Command::new(rustc)
+        .args(["--edition", "2018", "--emit=metadata", "--color", "never", "--crate-name", name])
+        .arg("--out-dir").arg(env!("CARGO_TARGET_TMPDIR"))
+        .arg(&source)
+        .output()
+        .expect("failed to run rustc");
+
+    assert!(!output.status.success(), "{} compiled, but the notes say it shouldn't", source.display());
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn assert_contains(stderr: &str, expected: &str) {
+    assert!(stderr.contains(expected), "expected {:?} in rustc output:\n{}", expected, stderr);
+}
+
+#[test]
+fn double_move() {
+    let stderr = compile_fail("double_move");
+    assert_contains(&stderr, "error[E0382]: use of moved value: `s`");
+    assert_contains(&stderr, "value moved here");
+    assert_contains(&stderr, "value used here after move");
+}
+
+#[test]
+fn move_out_of_index() {
+    let stderr = compile_fail("move_out_of_index");
+    assert_contains(&stderr, "error[E0507]: cannot move out of index of `Vec<String>`");
+    assert_contains(&stderr, "help: consider borrowing here");
+    assert_contains(&stderr, "let third = &v[2];");
+}
+
+#[test]
+fn move_field_out_of_index() {
+    let stderr = compile_fail("move_field_out_of_index");
+    assert_contains(&stderr, "error[E0507]: cannot move out of index of `Vec<Person>`");
+    assert_contains(&stderr, "help: consider borrowing here");
+    assert_contains(&stderr, "let first_name = &composers[0].name;");
+}
+
+#[test]
+fn use_after_move() {
+    let stderr = compile_fail("use_after_move");
+    assert_contains(&stderr, "error[E0382]: borrow of moved value: `l`");
+    assert_contains(&stderr, "value moved here");
+    assert_contains(&stderr, "value borrowed here after move");
+}
+
+#[test]
+fn copy_string_label() {
+    let stderr = compile_fail("copy_string_label");
+    assert_contains(&stderr, "error[E0204]: the trait `Copy` cannot be implemented for this type");
+    assert_contains(&stderr, "this field does not implement `Copy`");
+}
+
+#[test]
+fn rc_mutability() {
+    let stderr = compile_fail("rc_mutability");
+    assert_contains(&stderr, "error[E0596]: cannot borrow data in an `Rc` as mutable");
+    assert_contains(&stderr, "cannot borrow as mutable");
+}